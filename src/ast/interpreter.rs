@@ -1,6 +1,7 @@
 use std::{collections::HashMap, rc::Rc};
 
 use crate::errors::BasicError;
+use crate::library::functions::{self, Function};
 use crate::library::types::boolean::NBoolean;
 use crate::library::types::number::NNumber;
 use crate::library::types::string::NString;
@@ -9,28 +10,42 @@ use crate::{errors::Error, library::object_type::ObjectType, tokenizer::token::T
 
 use super::nodes;
 
+/// Variables computed only when a template uses them, like the tags of a file
+pub trait LazyVars {
+    fn get(&mut self, name: &str) -> Result<Option<String>, Box<dyn Error>>;
+
+    /// Hash of the content of the file
+    fn hash(&mut self, algorithm: &str) -> Result<String, Box<dyn Error>>;
+}
+
 pub struct Interpreter {
     scope: HashMap<String, String>,
-    count: i32,
     cap_count: usize,
+    functions: HashMap<&'static str, Function>,
+    function_context: functions::Context,
 }
 
 impl Interpreter {
     pub fn new() -> Self {
         Self {
             scope: HashMap::new(),
-            count: 0,
             cap_count: 0,
+            functions: functions::registry(),
+            function_context: functions::Context::default(),
         }
     }
 
     fn insert_special_vars(&mut self) {
-        self.scope
-            .insert(String::from("#count"), self.count.to_string());
         self.scope
             .insert(String::from("#cap_count"), self.cap_count.to_string());
     }
 
+    fn insert_file_vars(&mut self, file_vars: &HashMap<&str, String>) {
+        for (key, val) in file_vars {
+            self.scope.insert(String::from("#") + key, val.to_owned());
+        }
+    }
+
     fn insert_captures(&mut self, captures: &HashMap<String, &str>) {
         for (key, val) in captures {
             if key.parse::<i8>().is_ok() {
@@ -53,17 +68,30 @@ impl Interpreter {
         Ok(())
     }
 
+    fn get_variable(&mut self, name: &str) -> Result<Option<String>, Box<dyn Error>> {
+        if let Some(val) = self.scope.get(name) {
+            return Ok(Some(val.to_owned()));
+        }
+
+        match &mut self.function_context.lazy_vars {
+            Some(lazy_vars) => lazy_vars.get(name),
+            None => Ok(None),
+        }
+    }
+
     pub fn execute(
         &mut self,
         captures: &HashMap<String, &str>,
+        file_vars: &HashMap<&str, String>,
+        lazy_vars: Box<dyn LazyVars>,
         node: Rc<dyn nodes::ExecutableNode>,
     ) -> Result<ObjectType, Box<dyn Error>> {
         self.cap_count = captures.len();
+        self.function_context.lazy_vars = Some(lazy_vars);
         self.insert_captures(captures);
+        self.insert_file_vars(file_vars);
         self.insert_special_vars();
-        let res = node.execute(self);
-        self.count += 1;
-        res
+        node.execute(self)
     }
 }
 
@@ -138,6 +166,21 @@ impl nodes::ExecutableNode for nodes::NodeFor {
     }
 }
 
+impl nodes::ExecutableNode for nodes::NodeFunctionCall {
+    fn execute(&self, i: &mut Interpreter) -> Result<ObjectType, Box<dyn Error>> {
+        let mut args = Vec::new();
+        for arg in &self.args {
+            args.push(arg.execute(i)?);
+        }
+
+        let function = i
+            .functions
+            .get(self.name.as_str())
+            .ok_or_else(|| BasicError::new(format!("Unknown function: {}", self.name)))?;
+        function.call(&mut i.function_context, &self.name, args)
+    }
+}
+
 impl nodes::ExecutableNode for nodes::NodeContent {
     fn execute(&self, interpreter: &mut Interpreter) -> Result<ObjectType, Box<dyn Error>> {
         let mut inner_value = String::from(&self.content);
@@ -160,11 +203,10 @@ impl nodes::ExecutableNode for nodes::NodeIdentifer {
         }
 
         let capture = i
-            .scope
-            .get(&self.content)
+            .get_variable(&self.content)?
             .ok_or_else(|| BasicError::new(format!("Couldn't find variable: {}", &self.content)))?;
         Ok(ObjectType::NString(NString {
-            inner_value: capture.to_owned(),
+            inner_value: capture,
         }))
     }
 }
@@ -223,23 +265,139 @@ impl nodes::ExecutableNode for nodes::NodeNumber {
     }
 }
 
+impl nodes::ExecutableNode for nodes::NodeBoolean {
+    fn execute(&self, _: &mut Interpreter) -> Result<ObjectType, Box<dyn Error>> {
+        Ok(ObjectType::NBoolean(NBoolean {
+            inner_value: self.content,
+        }))
+    }
+}
+
 impl nodes::ExecutableNode for nodes::NodeCondition {
     fn execute(&self, i: &mut Interpreter) -> Result<ObjectType, Box<dyn Error>> {
-        let inner_value = match self.left.execute(i)? {
-            ObjectType::NBoolean(n) => {
-                equal_utils::partial_eq(&self.operator, n, self.right.execute(i)?.into_boolean()?)?
-            }
-            ObjectType::NString(n) => {
-                equal_utils::partial_eq(&self.operator, n, self.right.execute(i)?.into_string()?)?
-            }
-            ObjectType::NNumber(n) => {
-                equal_utils::partial_ord(&self.operator, n, self.right.execute(i)?.into_number()?)?
+        let left = self.left.execute(i)?;
+        let right = self.right.execute(i)?;
+        let inner_value = compare(&self.operator, left, right)?;
+        Ok(ObjectType::NBoolean(NBoolean { inner_value }))
+    }
+}
+
+/// The right side is converted to the type of the left side
+fn compare(
+    operator: &TokenType,
+    left: ObjectType,
+    right: ObjectType,
+) -> Result<bool, Box<dyn Error>> {
+    if *operator == TokenType::TildeEqualSign {
+        let left = left.into_string()?.inner_value.to_lowercase();
+        let right = right.into_string()?.inner_value.to_lowercase();
+        return Ok(left == right);
+    }
+
+    match (left, right) {
+        // Captures are strings, ordering them with a number compares them as numbers
+        (left @ ObjectType::NString(_), right @ ObjectType::NNumber(_))
+            if !equal_utils::is_equality(operator) =>
+        {
+            equal_utils::partial_ord(operator, left.into_number()?, right.into_number()?)
+        }
+        (ObjectType::NBoolean(n), right) => {
+            equal_utils::partial_ord(operator, n, right.into_boolean()?)
+        }
+        (ObjectType::NString(n), right) => {
+            equal_utils::partial_ord(operator, n, right.into_string()?)
+        }
+        (ObjectType::NNumber(n), right) => {
+            equal_utils::partial_ord(operator, n, right.into_number()?)
+        }
+        (ObjectType::NDate(n), right) => equal_utils::partial_ord(operator, n, right.into_date()?),
+    }
+}
+
+impl nodes::ExecutableNode for nodes::NodeLogical {
+    fn execute(&self, i: &mut Interpreter) -> Result<ObjectType, Box<dyn Error>> {
+        // The right side is only executed when it decides the result
+        let left = self.left.execute(i)?.into_boolean()?.inner_value;
+        let inner_value = match self.operator {
+            TokenType::DoubleAmpersand => {
+                left && self.right.execute(i)?.into_boolean()?.inner_value
             }
+            TokenType::DoublePipe => left || self.right.execute(i)?.into_boolean()?.inner_value,
+            _ => return Err(BasicError::new("not a logical operator".to_owned())),
         };
 
         Ok(ObjectType::NBoolean(NBoolean { inner_value }))
     }
 }
+
+impl nodes::ExecutableNode for nodes::NodeNot {
+    fn execute(&self, i: &mut Interpreter) -> Result<ObjectType, Box<dyn Error>> {
+        let inner_value = !self.content.execute(i)?.into_boolean()?.inner_value;
+        Ok(ObjectType::NBoolean(NBoolean { inner_value }))
+    }
+}
+
+impl nodes::ExecutableNode for nodes::NodeMatch {
+    fn execute(&self, i: &mut Interpreter) -> Result<ObjectType, Box<dyn Error>> {
+        // Patterns are tried in order and only the content of the first arm matching is executed
+        let value = self.value.execute(i)?;
+        for arm in &self.arms {
+            for pattern in &arm.patterns {
+                if match_pattern(i, pattern, &value)? {
+                    return arm.content.execute(i);
+                }
+            }
+        }
+
+        Err(BasicError::new(format!(
+            "No match arm for {}",
+            value.into_string()?.inner_value
+        )))
+    }
+}
+
+fn match_pattern(
+    i: &mut Interpreter,
+    pattern: &nodes::MatchPattern,
+    value: &ObjectType,
+) -> Result<bool, Box<dyn Error>> {
+    // A value that can't be converted to the type of the pattern doesn't match it
+    let matches = |operator: TokenType, other: ObjectType| {
+        compare(&operator, value.clone(), other).unwrap_or(false)
+    };
+
+    Ok(match pattern {
+        nodes::MatchPattern::Any => true,
+        nodes::MatchPattern::Value(node) => matches(TokenType::DoubleEqualSign, node.execute(i)?),
+        nodes::MatchPattern::Regex(node) => {
+            let pattern = node.execute(i)?.into_string()?.inner_value;
+            let value = value.clone().into_string()?.inner_value;
+            i.function_context.regex(&pattern)?.is_match(&value)
+        }
+        nodes::MatchPattern::Range {
+            from,
+            to,
+            inclusive,
+        } => {
+            if let Some(from) = from {
+                if !matches(TokenType::GreaterThanEqualSign, from.execute(i)?) {
+                    return Ok(false);
+                }
+            }
+
+            let operator = if *inclusive {
+                TokenType::LessThanEqualSign
+            } else {
+                TokenType::LessThanSign
+            };
+            match to {
+                Some(to) => matches(operator, to.execute(i)?),
+                None => true,
+            }
+        }
+    })
+}
+
 impl nodes::ExecutableNode for nodes::NodeTernary {
     fn execute(&self, i: &mut Interpreter) -> Result<ObjectType, Box<dyn Error>> {
         let cond = self.condition.execute(i)?.into_boolean()?;
@@ -269,3 +427,52 @@ impl nodes::ExecutableNode for nodes::NodeKeyword {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{ast::parser::Parser, tokenizer::lexer::Lexer};
+
+    use super::*;
+
+    struct NoVars;
+
+    impl LazyVars for NoVars {
+        fn get(&mut self, _: &str) -> Result<Option<String>, Box<dyn Error>> {
+            Ok(None)
+        }
+
+        fn hash(&mut self, _: &str) -> Result<String, Box<dyn Error>> {
+            Err(BasicError::new("There is no file to hash".to_owned()))
+        }
+    }
+
+    /// Interprets the template with #1 set to capture
+    fn render(template: &str, capture: &str) -> String {
+        let tokens = Lexer::new(template.to_owned()).tokenize();
+        let node = Parser::new(tokens, template.to_owned()).parse().unwrap();
+        let captures = HashMap::from([("1".to_owned(), capture)]);
+        Interpreter::new()
+            .execute(&captures, &HashMap::new(), Box::new(NoVars), node)
+            .and_then(|value| value.into_string())
+            .unwrap()
+            .inner_value
+    }
+
+    #[test]
+    fn match_range() {
+        let template = r#"[match #1 { ..5 => "low", 5..=20 => "mid", 21.. => "high" }]"#;
+        assert_eq!(render(template, "4"), "low");
+        assert_eq!(render(template, "5"), "mid");
+        assert_eq!(render(template, "20"), "mid");
+        assert_eq!(render(template, "21"), "high");
+    }
+
+    #[test]
+    fn match_non_numeric_value_falls_through_ranges() {
+        let template = r#"[match #1 { ~"^a$" => "A", ..5 => "n", 10..=20 => "m", _ => "other" }]"#;
+        assert_eq!(render(template, "a"), "A");
+        assert_eq!(render(template, "3"), "n");
+        assert_eq!(render(template, "b"), "other");
+        assert_eq!(render(template, "abc"), "other");
+    }
+}
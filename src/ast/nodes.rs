@@ -29,6 +29,12 @@ pub struct NodeFor {
     pub content: Rc<dyn ExecutableNode>,
 }
 
+#[derive(Debug)]
+pub struct NodeFunctionCall {
+    pub name: String,
+    pub args: Vec<Rc<dyn ExecutableNode>>,
+}
+
 #[derive(Debug)]
 pub struct NodeCondition {
     pub operator: TokenType,
@@ -36,6 +42,18 @@ pub struct NodeCondition {
     pub right: Rc<dyn ExecutableNode>,
 }
 
+#[derive(Debug)]
+pub struct NodeLogical {
+    pub operator: TokenType,
+    pub left: Rc<dyn ExecutableNode>,
+    pub right: Rc<dyn ExecutableNode>,
+}
+
+#[derive(Debug)]
+pub struct NodeNot {
+    pub content: Rc<dyn ExecutableNode>,
+}
+
 #[derive(Debug)]
 pub struct NodeContent {
     pub content: String,
@@ -61,6 +79,11 @@ pub struct NodeKeyword {
     pub options: Vec<Rc<dyn ExecutableNode>>,
 }
 
+#[derive(Debug)]
+pub struct NodeBoolean {
+    pub content: bool,
+}
+
 #[derive(Debug)]
 pub struct NodeNumber {
     pub content: f64,
@@ -77,3 +100,30 @@ pub struct NodeTernary {
     pub left: Rc<dyn ExecutableNode>,
     pub right: Rc<dyn ExecutableNode>,
 }
+
+#[derive(Debug)]
+pub enum MatchPattern {
+    /// _
+    Any,
+    Value(Rc<dyn ExecutableNode>),
+    /// ~"regex"
+    Regex(Rc<dyn ExecutableNode>),
+    /// from..to or from..=to, both ends are optional
+    Range {
+        from: Option<Rc<dyn ExecutableNode>>,
+        to: Option<Rc<dyn ExecutableNode>>,
+        inclusive: bool,
+    },
+}
+
+#[derive(Debug)]
+pub struct MatchArm {
+    pub patterns: Vec<MatchPattern>,
+    pub content: Rc<dyn ExecutableNode>,
+}
+
+#[derive(Debug)]
+pub struct NodeMatch {
+    pub value: Rc<dyn ExecutableNode>,
+    pub arms: Vec<MatchArm>,
+}
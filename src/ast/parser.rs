@@ -13,6 +13,8 @@ use super::{
     nodes::{self, NodeString},
 };
 
+const NAMESPACES: [&str; 2] = ["#exif", "#tag"];
+
 pub struct Parser {
     base_parser: BaseParser,
 }
@@ -88,8 +90,16 @@ impl Parser {
             }
         }
 
+        let mut content = token.content;
+        if NAMESPACES.contains(&content.as_str()) {
+            // Variables of a namespace are written #namespace.Name
+            self.base_parser.expect(TokenType::Dot)?;
+            let name = self.base_parser.expect(TokenType::Identifier)?;
+            content = format!("{}.{}", content, name.content);
+        }
+
         Ok(Rc::new(nodes::NodeIdentifer {
-            content: token.content,
+            content,
             use_for_name,
         }))
     }
@@ -113,12 +123,34 @@ impl Parser {
         Ok(Rc::new(nodes::NodeNumber { content }))
     }
 
+    pub fn parse_boolean(&mut self) -> Result<Rc<dyn nodes::ExecutableNode>, Box<dyn Error>> {
+        let token = self
+            .base_parser
+            .expect_m(vec![TokenType::KeyTrue, TokenType::KeyFalse])?;
+        Ok(Rc::new(nodes::NodeBoolean {
+            content: token.r#type == TokenType::KeyTrue,
+        }))
+    }
+
     pub fn parse_basic_type(&mut self) -> Result<Rc<dyn nodes::ExecutableNode>, Box<dyn Error>> {
+        let boolean = self.parse_boolean();
+        if boolean.is_ok() {
+            return boolean;
+        }
+
+        if self.is_match() {
+            return self.parse_match();
+        }
+
         let keyword = self.parse_keyword();
         if keyword.is_ok() {
             return keyword;
         }
 
+        if self.is_function_call() {
+            return self.parse_function_call();
+        }
+
         let identifer = self.parse_identifier(false);
         if identifer.is_ok() {
             return identifer;
@@ -147,6 +179,8 @@ impl Parser {
                     vec![
                         TokenType::KeyNumber,
                         TokenType::KeyString,
+                        TokenType::KeyTrue,
+                        TokenType::KeyFalse,
                         TokenType::Identifier,
                         TokenType::String,
                         TokenType::Number
@@ -161,6 +195,118 @@ impl Parser {
         ))
     }
 
+    fn is_function_call(&mut self) -> bool {
+        let chain_reader = &mut self.base_parser.chain_reader;
+        chain_reader
+            .get_current()
+            .is_some_and(|t| t.r#type == TokenType::Identifier)
+            && chain_reader
+                .get_next()
+                .is_some_and(|t| t.r#type == TokenType::ParentL)
+    }
+
+    pub fn parse_function_call(&mut self) -> Result<Rc<dyn nodes::ExecutableNode>, Box<dyn Error>> {
+        let name = self.base_parser.expect(TokenType::Identifier)?;
+        self.base_parser.expect(TokenType::ParentL)?;
+        let mut args = Vec::new();
+        if self.base_parser.expect(TokenType::ParentR).is_err() {
+            loop {
+                args.push(self.parse_ternary()?);
+                let token = self
+                    .base_parser
+                    .expect_m(vec![TokenType::Comma, TokenType::ParentR])?;
+                if token.r#type == TokenType::ParentR {
+                    break;
+                }
+            }
+        }
+
+        Ok(Rc::new(nodes::NodeFunctionCall {
+            name: name.content,
+            args,
+        }))
+    }
+
+    /// match is only a keyword when a value follows it, otherwise it can be a capture group name
+    fn is_match(&mut self) -> bool {
+        let chain_reader = &mut self.base_parser.chain_reader;
+        chain_reader
+            .get_current()
+            .is_some_and(|t| t.r#type == TokenType::Identifier && t.content == "match")
+            && chain_reader.get_next().is_some_and(|t| {
+                matches!(
+                    t.r#type,
+                    TokenType::Identifier
+                        | TokenType::String
+                        | TokenType::Number
+                        | TokenType::ParentL
+                        | TokenType::ExclamationMark
+                        | TokenType::KeyNumber
+                        | TokenType::KeyString
+                        | TokenType::KeyTrue
+                        | TokenType::KeyFalse
+                )
+            })
+    }
+
+    /// match value { pattern | pattern => content, ..., _ => content }
+    pub fn parse_match(&mut self) -> Result<Rc<dyn nodes::ExecutableNode>, Box<dyn Error>> {
+        self.base_parser.expect(TokenType::Identifier)?;
+        let value = self.parse_ternary()?;
+        self.base_parser.expect(TokenType::BracketL)?;
+        let mut arms = Vec::new();
+        while self.base_parser.expect(TokenType::BracketR).is_err() {
+            let mut patterns = vec![self.parse_match_pattern()?];
+            while self.base_parser.expect(TokenType::Pipe).is_ok() {
+                patterns.push(self.parse_match_pattern()?);
+            }
+
+            self.base_parser.expect(TokenType::Arrow)?;
+            let content = self.parse_ternary()?;
+            arms.push(nodes::MatchArm { patterns, content });
+
+            // The comma after the last arm is optional
+            if self.base_parser.expect(TokenType::Comma).is_err() {
+                self.base_parser.expect(TokenType::BracketR)?;
+                break;
+            }
+        }
+
+        Ok(Rc::new(nodes::NodeMatch { value, arms }))
+    }
+
+    fn parse_match_pattern(&mut self) -> Result<nodes::MatchPattern, Box<dyn Error>> {
+        let current = self.base_parser.chain_reader.get_current();
+        if current.is_some_and(|t| t.r#type == TokenType::Identifier && t.content == "_") {
+            self.base_parser.any()?;
+            return Ok(nodes::MatchPattern::Any);
+        }
+
+        if self.base_parser.expect(TokenType::Tilde).is_ok() {
+            return Ok(nodes::MatchPattern::Regex(self.parse_basic_type()?));
+        }
+
+        let from = match self.base_parser.chain_reader.get_current() {
+            Some(t) if t.r#type == TokenType::DoubleDot => None,
+            _ => Some(self.parse_binary_operation()?),
+        };
+        if self.base_parser.expect(TokenType::DoubleDot).is_err() {
+            return Ok(nodes::MatchPattern::Value(from.unwrap()));
+        }
+
+        let inclusive = self.base_parser.expect(TokenType::EqualSign).is_ok();
+        let to = match self.base_parser.chain_reader.get_current() {
+            Some(t) if matches!(t.r#type, TokenType::Arrow | TokenType::Pipe) => None,
+            _ => Some(self.parse_binary_operation()?),
+        };
+
+        Ok(nodes::MatchPattern::Range {
+            from,
+            to,
+            inclusive,
+        })
+    }
+
     pub fn parse_keyword(&mut self) -> Result<Rc<dyn nodes::ExecutableNode>, Box<dyn Error>> {
         let keyword = self
             .base_parser
@@ -282,7 +428,7 @@ impl Parser {
     }
 
     pub fn parse_ternary(&mut self) -> Result<Rc<dyn nodes::ExecutableNode>, Box<dyn Error>> {
-        let condition = self.parse_condition()?;
+        let condition = self.parse_or()?;
         let token = self.base_parser.expect_m(vec![
             TokenType::QuestionMark,
             TokenType::QuestionMarkGreaterThan,
@@ -310,6 +456,44 @@ impl Parser {
         Ok(condition)
     }
 
+    pub fn parse_or(&mut self) -> Result<Rc<dyn nodes::ExecutableNode>, Box<dyn Error>> {
+        let mut left = self.parse_and()?;
+        while let Ok(operator) = self.base_parser.expect(TokenType::DoublePipe) {
+            let right = self.parse_and()?;
+            left = Rc::new(nodes::NodeLogical {
+                operator: operator.r#type,
+                left,
+                right,
+            });
+        }
+
+        Ok(left)
+    }
+
+    pub fn parse_and(&mut self) -> Result<Rc<dyn nodes::ExecutableNode>, Box<dyn Error>> {
+        let mut left = self.parse_not()?;
+        while let Ok(operator) = self.base_parser.expect(TokenType::DoubleAmpersand) {
+            let right = self.parse_not()?;
+            left = Rc::new(nodes::NodeLogical {
+                operator: operator.r#type,
+                left,
+                right,
+            });
+        }
+
+        Ok(left)
+    }
+
+    /// ! applies to the whole comparison after it
+    pub fn parse_not(&mut self) -> Result<Rc<dyn nodes::ExecutableNode>, Box<dyn Error>> {
+        if self.base_parser.expect(TokenType::ExclamationMark).is_ok() {
+            let content = self.parse_not()?;
+            return Ok(Rc::new(nodes::NodeNot { content }));
+        }
+
+        self.parse_condition()
+    }
+
     pub fn parse_condition(&mut self) -> Result<Rc<dyn nodes::ExecutableNode>, Box<dyn Error>> {
         let mut left = self.parse_binary_operation()?;
         while let Ok(operator) = self.base_parser.expect_m(vec![
@@ -318,6 +502,8 @@ impl Parser {
             TokenType::GreaterThanSign,
             TokenType::GreaterThanEqualSign,
             TokenType::DoubleEqualSign,
+            TokenType::NotEqualSign,
+            TokenType::TildeEqualSign,
         ]) {
             let right = self.parse_binary_operation()?;
             left = Rc::new(nodes::NodeCondition {
@@ -0,0 +1,467 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use crate::{
+    ast::interpreter::LazyVars,
+    errors::{BasicError, Error},
+    utils::string_utils,
+};
+
+use super::{
+    object_type::ObjectType,
+    types::{
+        boolean::NBoolean,
+        date::{self, NDate},
+        number::NNumber,
+        string::NString,
+    },
+};
+
+/// Longer than any path, pad, repeat and format refuse to build longer strings
+const MAX_LENGTH: usize = 4096;
+
+pub type FunctionResult = Result<ObjectType, Box<dyn Error>>;
+type Call = fn(&mut Context, Vec<ObjectType>) -> FunctionResult;
+/// Required arguments, then the optional ones
+type SplitArgs<const R: usize, const O: usize> = ([ObjectType; R], [Option<ObjectType>; O]);
+
+/// State shared by the function calls of a run
+#[derive(Default)]
+pub struct Context {
+    regexes: HashMap<String, Regex>,
+    /// Variables of the file being renamed
+    pub lazy_vars: Option<Box<dyn LazyVars>>,
+}
+
+impl Context {
+    /// Patterns are compiled once per run
+    pub fn regex(&mut self, pattern: &str) -> Result<&Regex, Box<dyn Error>> {
+        if !self.regexes.contains_key(pattern) {
+            let regex = Regex::new(pattern)
+                .map_err(|e| BasicError::new(format!("Invalid regex {}: {}", pattern, e)))?;
+            self.regexes.insert(pattern.to_owned(), regex);
+        }
+
+        Ok(&self.regexes[pattern])
+    }
+}
+
+/// Function callable from a template with name(arg, ...)
+pub struct Function {
+    min_args: usize,
+    max_args: usize,
+    call: Call,
+}
+
+impl Function {
+    fn new(min_args: usize, max_args: usize, call: Call) -> Self {
+        Self {
+            min_args,
+            max_args,
+            call,
+        }
+    }
+
+    pub fn call(&self, context: &mut Context, name: &str, args: Vec<ObjectType>) -> FunctionResult {
+        if args.len() < self.min_args || args.len() > self.max_args {
+            let expected = if self.min_args == self.max_args {
+                self.min_args.to_string()
+            } else {
+                format!("{} to {}", self.min_args, self.max_args)
+            };
+
+            return Err(BasicError::new(format!(
+                "{} expects {} argument{}, got {}",
+                name,
+                expected,
+                if self.max_args == 1 { "" } else { "s" },
+                args.len()
+            )));
+        }
+
+        (self.call)(context, args)
+    }
+}
+
+pub fn registry() -> HashMap<&'static str, Function> {
+    HashMap::from([
+        ("upper", Function::new(1, 1, upper)),
+        ("lower", Function::new(1, 1, lower)),
+        ("title", Function::new(1, 1, title)),
+        ("trim", Function::new(1, 2, trim)),
+        ("pad", Function::new(2, 4, pad)),
+        ("replace", Function::new(3, 3, replace)),
+        ("slice", Function::new(2, 3, slice)),
+        ("len", Function::new(1, 1, len)),
+        ("repeat", Function::new(2, 2, repeat)),
+        ("starts_with", Function::new(2, 2, starts_with)),
+        ("ends_with", Function::new(2, 2, ends_with)),
+        ("contains", Function::new(2, 2, contains)),
+        ("natural_cmp", Function::new(2, 2, natural_cmp)),
+        ("format", Function::new(2, 2, format)),
+        ("re_replace", Function::new(3, 3, re_replace)),
+        ("re_match", Function::new(2, 2, re_match)),
+        ("re_find", Function::new(2, 3, re_find)),
+        ("date", Function::new(1, 2, date)),
+        ("now", Function::new(0, 1, now)),
+        ("parse_date", Function::new(2, 2, parse_date)),
+        ("hash", Function::new(1, 2, hash)),
+    ])
+}
+
+fn string(arg: ObjectType) -> Result<String, Box<dyn Error>> {
+    Ok(arg.into_string()?.inner_value)
+}
+
+fn number(arg: ObjectType) -> Result<f64, Box<dyn Error>> {
+    Ok(arg.into_number()?.inner_value)
+}
+
+fn string_result(inner_value: String) -> FunctionResult {
+    Ok(ObjectType::NString(NString { inner_value }))
+}
+
+fn boolean_result(inner_value: bool) -> FunctionResult {
+    Ok(ObjectType::NBoolean(NBoolean { inner_value }))
+}
+
+fn check_length(name: &str, length: usize) -> Result<(), Box<dyn Error>> {
+    if length > MAX_LENGTH {
+        return Err(BasicError::new(format!(
+            "{} result would be longer than {} characters",
+            name, MAX_LENGTH
+        )));
+    }
+
+    Ok(())
+}
+
+/// Splits the arguments into the required ones and the optional ones, the missing optional ones are None
+fn split_args<const R: usize, const O: usize>(
+    args: Vec<ObjectType>,
+) -> Result<SplitArgs<R, O>, Box<dyn Error>> {
+    let count = args.len();
+    if count < R || count > R + O {
+        let expected = if O == 0 {
+            R.to_string()
+        } else {
+            format!("{} to {}", R, R + O)
+        };
+        return Err(BasicError::new(format!(
+            "Expected {} arguments, got {}",
+            expected, count
+        )));
+    }
+
+    let mut args = args.into_iter();
+    let required: [ObjectType; R] = args
+        .by_ref()
+        .take(R)
+        .collect::<Vec<ObjectType>>()
+        .try_into()
+        .map_err(|_| BasicError::new(format!("Expected {} arguments", R)))?;
+    let optional = std::array::from_fn(|_| args.next());
+    Ok((required, optional))
+}
+
+fn upper(_: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([s], []) = split_args(args)?;
+    string_result(string(s)?.to_uppercase())
+}
+
+fn lower(_: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([s], []) = split_args(args)?;
+    string_result(string(s)?.to_lowercase())
+}
+
+/// Uppercase the first letter of every word, lowercase the others
+fn title(_: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([s], []) = split_args(args)?;
+    let mut inner_value = String::new();
+    let mut word_start = true;
+    for c in string(s)?.chars() {
+        if word_start {
+            inner_value.extend(c.to_uppercase());
+        } else {
+            inner_value.extend(c.to_lowercase());
+        }
+
+        word_start = !c.is_alphanumeric() && c != '\'';
+    }
+
+    string_result(inner_value)
+}
+
+/// Trims whitespaces, or the characters of the second argument
+fn trim(_: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([s], [chars]) = split_args(args)?;
+    let s = string(s)?;
+    match chars {
+        Some(chars) => {
+            let chars = string(chars)?;
+            string_result(s.trim_matches(|c| chars.contains(c)).to_owned())
+        }
+        None => string_result(s.trim().to_owned()),
+    }
+}
+
+/// pad(s, width, fill = " ", side = "left")
+fn pad(_: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([s, width], [fill, side]) = split_args(args)?;
+    let s = string(s)?;
+    let width = number(width)?.max(0.0) as usize;
+    check_length("pad", width)?;
+    let fill = fill.map(string).transpose()?.unwrap_or(" ".to_owned());
+    let mut fill_chars = fill.chars();
+    let (Some(fill), None) = (fill_chars.next(), fill_chars.next()) else {
+        return Err(BasicError::new(format!(
+            "pad fill must be a single character, got \"{}\"",
+            fill
+        )));
+    };
+
+    let padding = fill
+        .to_string()
+        .repeat(width.saturating_sub(s.chars().count()));
+    match side.map(string).transpose()?.as_deref() {
+        None | Some("left") => string_result(padding + &s),
+        Some("right") => string_result(s + &padding),
+        Some(side) => Err(BasicError::new(format!(
+            "pad side must be left or right, got {}",
+            side
+        ))),
+    }
+}
+
+fn replace(_: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([s, from, to], []) = split_args(args)?;
+    let s = string(s)?;
+    string_result(s.replace(&string(from)?, &string(to)?))
+}
+
+/// slice(s, start, end = len), indexes are in characters, negative ones start from the end
+fn slice(_: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([s, start], [end]) = split_args(args)?;
+    let chars: Vec<char> = string(s)?.chars().collect();
+    let len = chars.len() as i64;
+    let index = |n: f64| {
+        let n = n as i64;
+        let n = if n < 0 { len + n } else { n };
+        n.clamp(0, len) as usize
+    };
+
+    let start = index(number(start)?);
+    let end = match end {
+        Some(end) => index(number(end)?),
+        None => chars.len(),
+    };
+
+    string_result(chars[start..end.max(start)].iter().collect())
+}
+
+fn len(_: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([s], []) = split_args(args)?;
+    Ok(ObjectType::NNumber(NNumber {
+        inner_value: string(s)?.chars().count() as f64,
+    }))
+}
+
+fn repeat(_: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([s, n], []) = split_args(args)?;
+    let s = string(s)?;
+    let n = number(n)?.max(0.0) as usize;
+    check_length("repeat", s.chars().count().saturating_mul(n))?;
+    string_result(s.repeat(n))
+}
+
+fn starts_with(_: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([s, prefix], []) = split_args(args)?;
+    boolean_result(string(s)?.starts_with(&string(prefix)?))
+}
+
+fn ends_with(_: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([s, suffix], []) = split_args(args)?;
+    boolean_result(string(s)?.ends_with(&string(suffix)?))
+}
+
+fn contains(_: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([s, pattern], []) = split_args(args)?;
+    boolean_result(string(s)?.contains(&string(pattern)?))
+}
+
+/// Returns -1, 0 or 1 comparing numbers inside the strings by value, like --sort natural
+fn natural_cmp(_: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([a, b], []) = split_args(args)?;
+    let ordering = string_utils::natural_cmp(&string(a)?, &string(b)?);
+    Ok(ObjectType::NNumber(NNumber {
+        inner_value: ordering as i8 as f64,
+    }))
+}
+
+/// Formats a number with a spec like printf: [+][0][width][,][.precision][d|f]
+/// ex: format(7, "03d") gives 007, format(1234.5, "+,.2f") gives +1,234.50
+fn format(_: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([value, spec], []) = split_args(args)?;
+    let value = number(value)?;
+    let spec = string(spec)?;
+    let invalid = || BasicError::new(format!("Invalid format: {}", spec));
+
+    let mut rest = spec.as_str();
+    let plus = rest.strip_prefix('+').inspect(|r| rest = r).is_some();
+    let zero = rest.strip_prefix('0').inspect(|r| rest = r).is_some();
+    let width_len = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let width = match &rest[..width_len] {
+        "" => 0,
+        width => width.parse::<usize>().map_err(|_| invalid())?,
+    };
+    check_length("format", width)?;
+    rest = &rest[width_len..];
+    let thousands = rest.strip_prefix(',').inspect(|r| rest = r).is_some();
+    let precision = match rest.strip_prefix('.') {
+        Some(r) => {
+            let len = r.find(|c: char| !c.is_ascii_digit()).unwrap_or(r.len());
+            rest = &r[len..];
+            let precision = r[..len].parse::<usize>().map_err(|_| invalid())?;
+            check_length("format", precision)?;
+            Some(precision)
+        }
+        None => None,
+    };
+
+    let digits = match (rest, precision) {
+        ("d", None) => format!("{:.0}", value.abs().round()),
+        ("f", precision) => format!("{:.*}", precision.unwrap_or(6), value.abs()),
+        ("", Some(precision)) => format!("{:.*}", precision, value.abs()),
+        ("", None) => value.abs().to_string(),
+        _ => return Err(invalid()),
+    };
+
+    let (int_part, frac_part) = match digits.split_once('.') {
+        Some((int_part, frac_part)) => (int_part.to_owned(), format!(".{}", frac_part)),
+        None => (digits, String::new()),
+    };
+
+    let int_part = if thousands {
+        let len = int_part.len();
+        int_part
+            .chars()
+            .enumerate()
+            .fold(String::new(), |mut grouped, (i, c)| {
+                if i > 0 && (len - i) % 3 == 0 {
+                    grouped.push(',');
+                }
+
+                grouped.push(c);
+                grouped
+            })
+    } else {
+        int_part
+    };
+
+    let body = int_part + &frac_part;
+    // -0 is printed as 0
+    let sign = if value < 0.0 && body.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        "-"
+    } else if plus {
+        "+"
+    } else {
+        ""
+    };
+
+    let padding = width.saturating_sub(sign.len() + body.len());
+    let inner_value = if zero {
+        format!("{}{}{}", sign, "0".repeat(padding), body)
+    } else {
+        format!("{}{}{}", " ".repeat(padding), sign, body)
+    };
+
+    string_result(inner_value)
+}
+
+/// Replaces every match, the replacement can use the groups with $1 or ${name}
+fn re_replace(context: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([s, pattern, replacement], []) = split_args(args)?;
+    let s = string(s)?;
+    let regex = context.regex(&string(pattern)?)?;
+    string_result(
+        regex
+            .replace_all(&s, string(replacement)?.as_str())
+            .into_owned(),
+    )
+}
+
+fn re_match(context: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([s, pattern], []) = split_args(args)?;
+    let s = string(s)?;
+    boolean_result(context.regex(&string(pattern)?)?.is_match(&s))
+}
+
+/// re_find(s, pattern, group = 0), the group of the first match, by number or name
+/// Empty if nothing matches
+fn re_find(context: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([s, pattern], [group]) = split_args(args)?;
+    let s = string(s)?;
+    let regex = context.regex(&string(pattern)?)?;
+    let Some(captures) = regex.captures(&s) else {
+        return string_result(String::new());
+    };
+
+    let found = match group {
+        Some(ObjectType::NNumber(n)) => captures.get(n.inner_value as usize),
+        Some(group) => {
+            let group = string(group)?;
+            match group.parse::<usize>() {
+                Ok(n) => captures.get(n),
+                Err(_) => captures.name(&group),
+            }
+        }
+        None => captures.get(0),
+    };
+
+    string_result(found.map_or(String::new(), |m| m.as_str().to_owned()))
+}
+
+/// date(value, format = "%Y-%m-%d"), the value is a unix timestamp or a date string
+fn date(_: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([value], [format]) = split_args(args)?;
+    let date = value.into_date()?;
+    let format = match format {
+        Some(format) => string(format)?,
+        None => date::DEFAULT_FORMAT.to_owned(),
+    };
+    Ok(ObjectType::NString(date.format(&format)?))
+}
+
+fn now(_: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([], [format]) = split_args(args)?;
+    let format = match format {
+        Some(format) => string(format)?,
+        None => date::DEFAULT_FORMAT.to_owned(),
+    };
+    Ok(ObjectType::NString(NDate::now().format(&format)?))
+}
+
+/// Parses a date found in the file name, the time is midnight if the format has none
+fn parse_date(_: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([s, format], []) = split_args(args)?;
+    let date = NDate::parse(&string(s)?, &string(format)?)?;
+    Ok(ObjectType::NDate(date))
+}
+
+/// hash(algorithm, length), hash of the content of the file, only the first length characters if given
+fn hash(context: &mut Context, args: Vec<ObjectType>) -> FunctionResult {
+    let ([algorithm], [length]) = split_args(args)?;
+    let lazy_vars = context
+        .lazy_vars
+        .as_mut()
+        .ok_or_else(|| BasicError::new("There is no file to hash".to_owned()))?;
+    let mut inner_value = lazy_vars.hash(&string(algorithm)?)?;
+    if let Some(length) = length {
+        inner_value.truncate(number(length)?.max(0.0) as usize);
+    }
+
+    string_result(inner_value)
+}
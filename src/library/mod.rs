@@ -1,2 +1,3 @@
+pub mod functions;
 pub mod object_type;
 pub mod types;
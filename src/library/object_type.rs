@@ -1,11 +1,14 @@
 use crate::errors::{BasicError, Error};
 
-use super::types::{boolean::NBoolean, number::NNumber, string::NString};
+use super::types::{boolean::NBoolean, date::NDate, number::NNumber, string::NString};
 
+#[allow(clippy::enum_variant_names)]
+#[derive(Clone)]
 pub enum ObjectType {
     NBoolean(NBoolean),
     NString(NString),
     NNumber(NNumber),
+    NDate(NDate),
 }
 
 pub type IntoConv<T> = Result<T, Box<dyn Error>>;
@@ -15,6 +18,7 @@ impl ObjectType {
             ObjectType::NBoolean(n) => n.try_into(),
             ObjectType::NString(n) => Ok(n),
             ObjectType::NNumber(n) => n.try_into(),
+            ObjectType::NDate(n) => n.try_into(),
         }
     }
 
@@ -23,6 +27,7 @@ impl ObjectType {
             ObjectType::NBoolean(n) => n.try_into(),
             ObjectType::NString(n) => n.try_into(),
             ObjectType::NNumber(n) => Ok(n),
+            ObjectType::NDate(n) => n.try_into(),
         }
     }
 
@@ -30,9 +35,21 @@ impl ObjectType {
         match self {
             ObjectType::NBoolean(n) => Ok(n),
             ObjectType::NNumber(n) => n.try_into(),
+            ObjectType::NString(n) => n.try_into(),
             _ => Err(BasicError::new(
                 "conversion to boolean not supported".to_owned(),
             )),
         }
     }
+
+    pub fn into_date(self) -> IntoConv<NDate> {
+        match self {
+            ObjectType::NString(n) => n.try_into(),
+            ObjectType::NNumber(n) => n.try_into(),
+            ObjectType::NDate(n) => Ok(n),
+            _ => Err(BasicError::new(
+                "conversion to date not supported".to_owned(),
+            )),
+        }
+    }
 }
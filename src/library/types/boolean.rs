@@ -2,7 +2,7 @@ use crate::{errors::Error, library::object_type::IntoConv};
 
 use super::{number::NNumber, string::NString};
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct NBoolean {
     pub inner_value: bool,
 }
@@ -0,0 +1,106 @@
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Local, NaiveDate, NaiveDateTime, TimeZone,
+};
+
+use crate::{
+    errors::{BasicError, Error},
+    library::object_type::IntoConv,
+};
+
+use super::{number::NNumber, string::NString};
+
+pub const DEFAULT_FORMAT: &str = "%Y-%m-%d";
+const STRING_FORMATS: [&str; 3] = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", DEFAULT_FORMAT];
+
+/// Date and time in the local timezone
+#[derive(Clone, PartialEq, Eq, PartialOrd)]
+pub struct NDate {
+    pub inner_value: DateTime<Local>,
+}
+
+impl NDate {
+    pub fn now() -> Self {
+        Self {
+            inner_value: Local::now(),
+        }
+    }
+
+    /// Parses a date with a strftime format, the time is midnight if the format has none
+    pub fn parse(s: &str, format: &str) -> IntoConv<Self> {
+        let naive = NaiveDateTime::parse_from_str(s, format)
+            .or_else(|_| {
+                NaiveDate::parse_from_str(s, format).map(|d| d.and_time(Default::default()))
+            })
+            .map_err(|e| {
+                BasicError::new(format!("Couldn't parse date {} with {}: {}", s, format, e))
+            })?;
+
+        let inner_value = Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .ok_or_else(|| BasicError::new(format!("{} doesn't exist in the local timezone", s)))?;
+        Ok(Self { inner_value })
+    }
+
+    pub fn format(&self, format: &str) -> IntoConv<NString> {
+        let items: Vec<Item> = StrftimeItems::new(format).collect();
+        if items.contains(&Item::Error) {
+            return Err(BasicError::new(format!("Invalid date format: {}", format)));
+        }
+
+        Ok(NString {
+            inner_value: self
+                .inner_value
+                .format_with_items(items.into_iter())
+                .to_string(),
+        })
+    }
+}
+
+impl TryInto<NString> for NDate {
+    type Error = Box<dyn Error>;
+    fn try_into(self) -> IntoConv<NString> {
+        self.format(DEFAULT_FORMAT)
+    }
+}
+
+impl TryInto<NNumber> for NDate {
+    type Error = Box<dyn Error>;
+    fn try_into(self) -> IntoConv<NNumber> {
+        Ok(NNumber {
+            inner_value: self.inner_value.timestamp() as f64,
+        })
+    }
+}
+
+/// Numbers are seconds since the unix epoch
+impl TryInto<NDate> for NNumber {
+    type Error = Box<dyn Error>;
+    fn try_into(self) -> IntoConv<NDate> {
+        let inner_value = DateTime::from_timestamp(self.inner_value as i64, 0)
+            .ok_or_else(|| BasicError::new(format!("Invalid timestamp: {}", self.inner_value)))?
+            .with_timezone(&Local);
+        Ok(NDate { inner_value })
+    }
+}
+
+/// Strings are either seconds since the unix epoch or a date like 2024-01-31 12:00:00
+impl TryInto<NDate> for NString {
+    type Error = Box<dyn Error>;
+    fn try_into(self) -> IntoConv<NDate> {
+        let s = self.inner_value.trim();
+        if let Ok(timestamp) = s.parse::<f64>() {
+            return NNumber {
+                inner_value: timestamp,
+            }
+            .try_into();
+        }
+
+        let date = STRING_FORMATS
+            .iter()
+            .find_map(|format| NDate::parse(s, format).ok())
+            .ok_or_else(|| BasicError::new(format!("Couldn't convert {} to a date", s)))?;
+        Ok(date)
+    }
+}
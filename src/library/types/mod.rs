@@ -1,3 +1,4 @@
 pub mod boolean;
+pub mod date;
 pub mod number;
 pub mod string;
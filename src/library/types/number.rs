@@ -5,7 +5,7 @@ use crate::{
 
 use super::{boolean::NBoolean, string::NString};
 
-#[derive(PartialEq, PartialOrd)]
+#[derive(Clone, PartialEq, PartialOrd)]
 pub struct NNumber {
     pub inner_value: f64,
 }
@@ -1,10 +1,10 @@
-use super::number::NNumber;
+use super::{boolean::NBoolean, number::NNumber};
 use crate::{
     errors::{BasicError, Error},
     library::object_type::IntoConv,
 };
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct NString {
     pub inner_value: String,
 }
@@ -36,3 +36,22 @@ impl TryInto<NNumber> for NString {
         })
     }
 }
+
+/// Only "true" and "false" are booleans, like #is_dir
+impl TryInto<NBoolean> for NString {
+    type Error = Box<dyn Error>;
+    fn try_into(self) -> IntoConv<NBoolean> {
+        let inner_value = match self.inner_value.as_str() {
+            "true" => true,
+            "false" => false,
+            _ => {
+                return Err(BasicError::new(format!(
+                    "Couldn't convert {} to boolean",
+                    self.inner_value
+                )))
+            }
+        };
+
+        Ok(NBoolean { inner_value })
+    }
+}
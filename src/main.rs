@@ -1,46 +1,59 @@
 mod ast;
 mod errors;
 mod library;
+mod renamer;
 mod tokenizer;
 mod utils;
 
 use std::{
     collections::HashMap,
-    fs::{read_dir, rename},
-    io,
-    path::PathBuf,
+    fs::{canonicalize, File},
+    io::{self, BufReader},
+    path::{Path, PathBuf},
     process::exit,
     rc::Rc,
 };
 
 use ast::nodes::ExecutableNode;
-use clap::Parser;
-use errors::Error;
+use clap::{Args, Parser, Subcommand};
+use errors::{BasicError, Error};
 use indexmap::IndexMap;
+use owo_colors::OwoColorize;
 use regex::{Regex, RegexBuilder};
-use walkdir::WalkDir;
+use renamer::{
+    conflict::{self, ConflictStrategy},
+    counter::{Counter, CounterArgs},
+    duplicate, editor,
+    format::{self, PlanFormat, PlanRecord, Status},
+    interactive,
+    journal::Journal,
+    planner,
+    tags::FileTags,
+    transaction, vars,
+    walk::{WalkArgs, Walker},
+};
 
 use crate::{
     ast::{interpreter::Interpreter, parser},
     tokenizer::lexer,
+    utils::path_utils,
 };
 
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
+#[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Cli {
-    #[clap(value_parser)]
-    input: String,
+    #[clap(subcommand)]
+    command: Option<Command>,
 
-    #[clap(value_parser)]
-    output: String,
+    #[clap(value_parser, required = true)]
+    input: Option<String>,
 
-    /// Skip the preview (useful in scripts)
-    #[clap(short, long)]
-    skip: bool,
+    #[clap(value_parser, required = true)]
+    output: Option<String>,
 
-    /// Pretty_print the output
-    #[clap(short, long)]
-    pretty_print: bool,
+    #[clap(flatten)]
+    rename: RenameArgs,
 
     /// Case sensitive regex
     #[clap(short, long)]
@@ -56,29 +69,125 @@ struct Cli {
     #[clap(short = 'l', long)]
     path: Option<String>,
 
-    /// Recursive
-    /// Recursively get files in directory
+    #[clap(flatten)]
+    walk: WalkArgs,
+
+    #[clap(flatten)]
+    counter: CounterArgs,
+
+    /// Print the rename plan in a machine readable format and exit without renaming
+    #[clap(long, value_enum)]
+    format: Option<PlanFormat>,
+}
+
+/// Options used when renaming files
+#[derive(Args)]
+struct RenameArgs {
+    /// Skip the preview (useful in scripts)
     #[clap(short, long)]
-    recursive: bool,
+    skip: bool,
+
+    /// Pretty_print the output
+    #[clap(short, long)]
+    pretty_print: bool,
+
+    /// What to do when a new name is taken by a file that is not renamed
+    #[clap(long, value_enum, default_value_t = ConflictStrategy::Abort)]
+    on_conflict: ConflictStrategy,
+
+    /// Create missing directories
+    /// A `/` in the output moves the file to another directory
+    #[clap(long)]
+    create_dirs: bool,
 
-    /// Depth limit
-    /// Specify a depth limit, if not, unlimited (used with -r)
+    /// Edit the new names in $EDITOR before renaming
     #[clap(short, long)]
-    depth_limit: Option<usize>,
+    edit: bool,
+
+    /// Ask for every file before renaming it
+    #[clap(short, long)]
+    interactive: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Apply a rename plan printed by --format json
+    Apply {
+        /// Plan file, one JSON object per line
+        #[clap(value_parser)]
+        plan: PathBuf,
+
+        /// Path
+        /// Change the base directory the paths of the plan are relative to
+        #[clap(short = 'l', long)]
+        path: Option<String>,
+
+        #[clap(flatten)]
+        rename: RenameArgs,
+    },
+
+    /// Undo the last applied rename batch
+    Undo {
+        /// Id of an earlier batch to undo instead of the last one (see --list)
+        #[clap(value_parser)]
+        id: Option<String>,
+
+        /// List the batches recorded in the journal
+        #[clap(long)]
+        list: bool,
+
+        /// Skip the preview (useful in scripts)
+        #[clap(short, long)]
+        skip: bool,
+    },
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     let cli = Cli::parse();
-    let regex = RegexBuilder::new(&cli.input)
+    match &cli.command {
+        Some(Command::Undo { id, list, skip }) => {
+            if let Err(e) = undo(id.as_deref(), *list, *skip) {
+                println!("{}", e.message());
+                exit(1);
+            }
+
+            return Ok(());
+        }
+        Some(Command::Apply { plan, path, rename }) => {
+            let base = canonicalize(path.as_deref().unwrap_or("./")).expect("Couldn't read dir");
+            let file_rename = match load_plan(&base, plan) {
+                Ok(file_rename) => file_rename,
+                Err(e) => {
+                    println!("{}", e.message());
+                    exit(1);
+                }
+            };
+
+            if file_rename.is_empty() {
+                println!("No files to rename, exiting");
+                exit(1);
+            }
+
+            let template = plan.display().to_string();
+            rename_files(&base, file_rename, rename, "apply", &template);
+            return Ok(());
+        }
+        None => (),
+    }
+
+    let input = cli.input.clone().unwrap_or_default();
+    let output = cli.output.clone().unwrap_or_default();
+    let regex = RegexBuilder::new(&input)
         .case_insensitive(!cli.case_sensitive)
         .build()
         .expect("Invalid regex");
 
     let path = cli.path.as_deref().unwrap_or("./");
-    let mut lex = lexer::Lexer::new(cli.output.clone());
+    let base = canonicalize(path).expect("Couldn't read dir");
+    let mut lex = lexer::Lexer::new(output.clone());
     let tokens = lex.tokenize();
 
-    let mut tree = parser::Parser::new(tokens, cli.output.clone());
+    let mut tree = parser::Parser::new(tokens, output.clone());
     let node_result = tree.parse();
     if let Err(e) = node_result {
         println!("{}", e.message());
@@ -86,82 +195,339 @@ fn main() -> Result<(), Box<dyn Error>> {
     }
 
     let node = node_result.unwrap();
-    let file_rename = run_interpreter(
-        path,
-        &regex,
-        &node,
-        cli.global,
-        cli.recursive,
-        cli.depth_limit,
-    );
-    if file_rename.is_empty() {
+    let paths = match Walker::new(&base, &cli.walk) {
+        Ok(walker) => walker.walk(),
+        Err(e) => {
+            println!("{}", e.message());
+            exit(1);
+        }
+    };
+
+    let results = run_interpreter(&base, paths, &regex, &node, cli.global, &cli.counter);
+    if results.is_empty() {
         println!("No files to rename, exiting");
         exit(1);
     }
 
-    // Detect duplicates
-    if find_duplicates(&file_rename) {
+    if let Some(format) = cli.format {
+        dry_run(&base, results, format);
+        return Ok(());
+    }
+
+    let mut file_rename = IndexMap::new();
+    for (path, result) in results {
+        match result {
+            Ok(new_path) => file_rename.insert(path, new_path),
+            Err(e) => {
+                println!("{}", e.message());
+                exit(1);
+            }
+        };
+    }
+
+    rename_files(&base, file_rename, &cli.rename, &input, &output);
+    Ok(())
+}
+
+fn rename_files(
+    base: &Path,
+    file_rename: IndexMap<PathBuf, PathBuf>,
+    args: &RenameArgs,
+    pattern: &str,
+    template: &str,
+) {
+    let mut file_rename = file_rename;
+    if args.edit {
+        file_rename = match editor::edit(base, &file_rename) {
+            Ok(file_rename) => file_rename,
+            Err(e) => {
+                println!("{}", e.message());
+                exit(1);
+            }
+        };
+
+        if file_rename.is_empty() {
+            println!("No files to rename, exiting");
+            exit(0);
+        }
+    }
+
+    if args.interactive {
+        file_rename = match interactive::ask(base, &file_rename) {
+            Ok(file_rename) => file_rename,
+            Err(e) => {
+                println!("{}", e.message());
+                exit(1);
+            }
+        };
+
+        if file_rename.is_empty() {
+            println!("No files to rename, exiting");
+            exit(0);
+        }
+    }
+
+    // Detect duplicates, identical files are renamed only once
+    let duplicates = duplicate::find(&file_rename);
+    if !duplicates.conflicting.is_empty() {
         println!("Found name duplicates, cannot process renaming");
+        for new_path in duplicates.conflicting.iter() {
+            println!("\t{}", path_utils::display(base, new_path));
+        }
+
         exit(1);
     }
 
-    if !cli.skip {
-        for (file_name, new_file_name) in file_rename.iter() {
-            if cli.pretty_print {
-                println!("{}\n ╰─> {}", file_name, new_file_name);
-            } else {
-                println!("{} -> {}", file_name, new_file_name);
-            }
+    for (old_path, kept_path) in duplicates.identical.iter() {
+        println!(
+            "{} is identical to {}, skipped",
+            path_utils::display(base, old_path),
+            path_utils::display(base, kept_path)
+        );
+        file_rename.shift_remove(old_path);
+    }
+
+    let planned: IndexMap<PathBuf, PathBuf> = file_rename
+        .into_iter()
+        .filter(|(old_path, new_path)| old_path != new_path)
+        .collect();
+    let mut renames = planned.clone();
+    let conflicts = match conflict::resolve(&mut renames, args.on_conflict) {
+        Ok(conflicts) => conflicts,
+        Err(e) => {
+            println!("{}", e.message());
+            exit(1);
         }
+    };
+
+    // Nothing left to rename, an empty batch in the journal would be picked by undo
+    if renames.is_empty() {
+        println!("No files to rename, exiting");
+        exit(0);
+    }
+
+    if !args.skip && !args.interactive {
+        for (old_path, new_path) in planned.iter() {
+            let new_path = renames.get(old_path).unwrap_or(new_path);
+            let mut new_file_name = path_utils::display(base, new_path);
+            if conflicts.contains_key(old_path) {
+                let note = match args.on_conflict {
+                    ConflictStrategy::Skip => "(conflict, skipped)",
+                    ConflictStrategy::Overwrite => "(conflict, overwrite)",
+                    _ => "(conflict, suffixed)",
+                };
+                new_file_name = format!("{} {}", new_file_name, note.red());
+            }
 
-        println!("Rename files ? (y\\N)");
-        let mut a = String::new();
-        io::stdin().read_line(&mut a).expect("Failed to read input");
+            print_rename(
+                &path_utils::display(base, old_path),
+                &new_file_name,
+                args.pretty_print,
+            );
+        }
 
-        if a.to_lowercase().trim() != "y" {
+        if !confirm("Rename files ? (y\\N)") {
             println!("Exiting without renaming files");
             exit(0);
         }
     }
 
-    for (file_name, new_file_name) in file_rename.iter() {
-        rename_file(path, file_name, new_file_name);
+    let options = transaction::Options {
+        overwrite: args.on_conflict == ConflictStrategy::Overwrite,
+        create_dirs: args.create_dirs,
+    };
+    let created_dirs = match transaction::apply(&renames, options) {
+        Ok(created_dirs) => created_dirs,
+        Err(e) => {
+            println!("{}", e.message());
+            exit(1);
+        }
+    };
+
+    println!("Done renaming {} files", renames.len());
+    // Created directories can be inside a renamed directory too
+    let created_dirs: Vec<PathBuf> = created_dirs
+        .iter()
+        .map(|dir| planner::final_path(&renames, dir))
+        .collect();
+    let renames = planner::finalize(&renames);
+    if let Err(e) =
+        Journal::open().and_then(|j| j.record(pattern, template, &renames, &created_dirs))
+    {
+        println!("Couldn't record the batch in the journal: {}", e.message());
+    }
+}
+
+fn load_plan(base: &Path, plan: &Path) -> Result<IndexMap<PathBuf, PathBuf>, Box<dyn Error>> {
+    let file = File::open(plan)
+        .map_err(|e| BasicError::new(format!("Couldn't read plan {}: {}", plan.display(), e)))?;
+
+    let mut file_rename = IndexMap::new();
+    for record in format::read_plan(BufReader::new(file))? {
+        let Some(target) = record.target else {
+            continue;
+        };
+
+        let old_path = path_utils::normalize(&base.join(&record.source));
+        let new_path = path_utils::normalize(&base.join(&target));
+        for path in [&old_path, &new_path] {
+            if !path.starts_with(base) {
+                return Err(BasicError::new(format!(
+                    "{} is outside of the base directory",
+                    path.display()
+                )));
+            }
+        }
+
+        if file_rename.insert(old_path, new_path).is_some() {
+            return Err(BasicError::new(format!(
+                "{} is renamed more than once",
+                record.source
+            )));
+        }
+    }
+
+    Ok(file_rename)
+}
+
+fn dry_run(
+    base: &Path,
+    results: IndexMap<PathBuf, Result<PathBuf, Box<dyn Error>>>,
+    format: PlanFormat,
+) {
+    let renames: IndexMap<PathBuf, PathBuf> = results
+        .iter()
+        .filter_map(|(old_path, r)| Some((old_path.clone(), r.as_ref().ok()?.clone())))
+        .collect();
+    let duplicates = duplicate::find(&renames);
+    let conflicts = conflict::find_conflicts(&renames);
+    let records: Vec<PlanRecord> = results
+        .into_iter()
+        .map(|(old_path, result)| {
+            let (target, status, error) = match result {
+                Ok(new_path) if duplicates.identical.contains_key(&old_path) => {
+                    (Some(new_path), Status::Identical, None)
+                }
+                Ok(new_path) if duplicates.conflicting.contains(&new_path) => (
+                    Some(new_path),
+                    Status::Error,
+                    Some("duplicate target".to_owned()),
+                ),
+                Ok(new_path) if new_path == old_path => (Some(new_path), Status::Unchanged, None),
+                Ok(new_path) if conflicts.contains_key(&old_path) => {
+                    (Some(new_path), Status::Conflict, None)
+                }
+                Ok(new_path) => (Some(new_path), Status::Rename, None),
+                Err(e) => (None, Status::Error, Some(e.message())),
+            };
+
+            PlanRecord {
+                source: path_utils::display(base, &old_path),
+                target: target.map(|t| path_utils::display(base, &t)),
+                status,
+                error,
+            }
+        })
+        .collect();
+
+    if let Err(e) = format::write_plan(&mut io::stdout().lock(), &records, format) {
+        println!("Couldn't write the plan: {}", e);
+        exit(1);
+    }
+}
+
+fn undo(id: Option<&str>, list: bool, skip: bool) -> Result<(), Box<dyn Error>> {
+    let journal = Journal::open()?;
+    if list {
+        for batch in journal.batches()?.iter().rev() {
+            println!("{}", batch.describe());
+        }
+
+        return Ok(());
+    }
+
+    let mut batch = journal.get(id)?;
+    if batch.undone {
+        return Err(BasicError::new(format!(
+            "Batch {} was already undone",
+            batch.id
+        )));
+    }
+
+    let problems = batch.check();
+    if !problems.is_empty() {
+        for problem in problems {
+            println!("{}", problem);
+        }
+
+        return Err(BasicError::new(format!(
+            "Cannot undo batch {}, files changed since it ran",
+            batch.id
+        )));
+    }
+
+    if !skip {
+        println!("{}", batch.describe());
+        for entry in batch.entries.iter() {
+            print_rename(
+                &entry.new_path.display().to_string(),
+                &entry.old_path.display().to_string(),
+                false,
+            );
+        }
+
+        if !confirm("Undo renames ? (y\\N)") {
+            println!("Exiting without undoing");
+            return Ok(());
+        }
     }
 
-    println!("Done renaming {} files", file_rename.len());
+    let renames: IndexMap<PathBuf, PathBuf> = batch
+        .entries
+        .iter()
+        .rev()
+        .map(|e| (e.new_path.clone(), e.old_path.clone()))
+        .collect();
+    let renames = planner::unfinalize(&renames);
+    transaction::apply(&renames, transaction::Options::default())?;
+    for problem in batch.remove_created_dirs() {
+        println!("{}", problem);
+    }
+
+    batch.undone = true;
+    journal.save(&batch)?;
+    println!("Done undoing {} files", batch.entries.len());
     Ok(())
 }
 
+fn print_rename(file_name: &str, new_file_name: &str, pretty_print: bool) {
+    if pretty_print {
+        println!("{}\n ╰─> {}", file_name, new_file_name);
+    } else {
+        println!("{} -> {}", file_name, new_file_name);
+    }
+}
+
+fn confirm(question: &str) -> bool {
+    println!("{}", question);
+    let mut a = String::new();
+    io::stdin().read_line(&mut a).expect("Failed to read input");
+
+    a.to_lowercase().trim() == "y"
+}
+
 fn run_interpreter(
-    path: &str,
+    base: &Path,
+    paths: Vec<PathBuf>,
     regex: &Regex,
     node: &Rc<dyn ExecutableNode>,
     global: bool,
-    recursive: bool,
-    depth_limit: Option<usize>,
-) -> IndexMap<String, String> {
-    let paths: Vec<PathBuf> = if recursive {
-        let walkdir_iter = if let Some(depth_limit_un) = depth_limit {
-            WalkDir::new(path).max_depth(depth_limit_un).into_iter()
-        } else {
-            WalkDir::new(path).into_iter()
-        };
-
-        walkdir_iter
-            .filter_map(|e| e.ok()) // Filter out Err values
-            .filter(|e| e.file_type().is_file()) // Keep only files
-            .map(|e| e.into_path()) // Convert DirEntry to PathBuf
-            .collect()
-    } else {
-        read_dir(path)
-            .expect("Couldn't read dir")
-            .filter_map(|e| e.ok()) // Filter out Err values
-            .map(|e| e.path()) // Extract PathBuf
-            .collect()
-    };
-
+    counter_args: &CounterArgs,
+) -> IndexMap<PathBuf, Result<PathBuf, Box<dyn Error>>> {
     let mut file_rename = IndexMap::new();
     let mut interpreter = Interpreter::new();
+    let mut count = Counter::new(counter_args);
+    let mut renamed_count = Counter::new(counter_args);
     for path in paths {
         let file_name = path
             .file_name()
@@ -170,8 +536,13 @@ fn run_interpreter(
             .expect("Couldn't get file_name")
             .to_owned();
 
+        let parent = path.parent().unwrap_or(base);
+        let mut file_vars = vars::file_vars(base, &path);
+        file_vars.insert("count", count.get(parent));
+        file_vars.insert("renamed_count", renamed_count.get(parent));
+
         let mut captures: HashMap<String, &str> = HashMap::new();
-        let mut count = 0;
+        let mut cap_index = 0;
         let start = if global { 1 } else { 0 };
         for cap in regex.captures_iter(&file_name) {
             for name in regex.capture_names().flatten() {
@@ -185,45 +556,51 @@ fn run_interpreter(
 
             for i in start..cap.len() {
                 if let Some(c) = cap.get(i) {
-                    captures.insert(count.to_string(), c.as_str());
-                    count += 1;
+                    captures.insert(cap_index.to_string(), c.as_str());
+                    cap_index += 1;
                 }
             }
         }
 
         if !captures.is_empty() {
-            let result = interpreter.execute(&captures, node.clone());
-
-            if let Err(e) = result {
-                println!("{}", e.message());
-                exit(1);
+            let new_path = render_path(&mut interpreter, base, &path, &captures, &file_vars, node);
+            count.advance(parent);
+            // Counts the planned renames, the ones dropped later on still use a number
+            if new_path.as_ref().is_ok_and(|p| p != &path) {
+                renamed_count.advance(parent);
             }
 
-            let sh = result.unwrap().into_string();
-            if let Err(e) = sh {
-                println!("{}", e.message());
-                exit(1);
-            }
-
-            file_rename.insert(file_name, sh.unwrap().inner_value.trim().to_owned());
+            file_rename.insert(path, new_path);
         }
     }
 
     file_rename
 }
 
-fn rename_file(path: &str, old_name: &str, new_name: &str) {
-    rename(path.to_owned() + old_name, path.to_owned() + new_name).expect("Couldn't rename file");
-}
-
-fn find_duplicates(file_rename: &IndexMap<String, String>) -> bool {
-    for (k, v) in file_rename.iter() {
-        for (k2, v2) in file_rename.iter() {
-            if v == v2 && k != k2 {
-                return true;
-            }
-        }
+fn render_path(
+    interpreter: &mut Interpreter,
+    base: &Path,
+    path: &Path,
+    captures: &HashMap<String, &str>,
+    file_vars: &HashMap<&str, String>,
+    node: &Rc<dyn ExecutableNode>,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let parent = path.parent().unwrap_or(base);
+    let new_name = interpreter
+        .execute(
+            captures,
+            file_vars,
+            Box::new(FileTags::new(path)),
+            node.clone(),
+        )?
+        .into_string()?;
+    let new_path = path_utils::normalize(&parent.join(new_name.inner_value.trim()));
+    if !new_path.starts_with(base) {
+        return Err(BasicError::new(format!(
+            "{} is outside of the base directory",
+            new_path.display()
+        )));
     }
 
-    false
+    Ok(new_path)
 }
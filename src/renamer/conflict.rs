@@ -0,0 +1,105 @@
+use std::{
+    collections::HashSet,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use indexmap::IndexMap;
+
+use crate::{
+    errors::{BasicError, Error},
+    utils::path_utils,
+};
+
+/// What to do when a target is taken by a file that is not part of the batch
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ConflictStrategy {
+    /// Don't rename the files with a conflicting target
+    Skip,
+    /// Don't rename anything
+    Abort,
+    /// Replace the existing files
+    Overwrite,
+    /// Add a suffix to the target: name (1).ext
+    AutoSuffix,
+}
+
+/// Returns the renames (source -> target) whose target is
+/// taken by a file that is not a source of the batch
+pub fn find_conflicts(renames: &IndexMap<PathBuf, PathBuf>) -> IndexMap<PathBuf, PathBuf> {
+    renames
+        .iter()
+        .filter(|(old_path, new_path)| {
+            old_path != new_path
+                && !renames.contains_key(*new_path)
+                && path_utils::is_taken(old_path, new_path)
+        })
+        .map(|(old_path, new_path)| (old_path.clone(), new_path.clone()))
+        .collect()
+}
+
+/// Applies the strategy to the renames, returns the conflicts found (source -> target)
+pub fn resolve(
+    renames: &mut IndexMap<PathBuf, PathBuf>,
+    strategy: ConflictStrategy,
+) -> Result<IndexMap<PathBuf, PathBuf>, Box<dyn Error>> {
+    let mut conflicts = find_conflicts(renames);
+    match strategy {
+        ConflictStrategy::Abort if !conflicts.is_empty() => {
+            let msg = conflicts
+                .values()
+                .map(|new_path| format!("{} already exists", new_path.display()))
+                .collect::<Vec<String>>()
+                .join("\n\t");
+            return Err(BasicError::new(format!(
+                "Found conflicts with existing files, cannot process renaming:\n\t{}",
+                msg
+            )));
+        }
+        ConflictStrategy::Skip => {
+            // A skipped source stays in place, which can make another target conflict
+            let mut found = conflicts.clone();
+            while !found.is_empty() {
+                for old_path in found.keys() {
+                    renames.shift_remove(old_path);
+                }
+
+                found = find_conflicts(renames);
+                conflicts.extend(found.clone());
+            }
+        }
+        ConflictStrategy::AutoSuffix => {
+            let mut targets: HashSet<PathBuf> = renames.values().cloned().collect();
+            for (old_path, new_path) in conflicts.iter() {
+                let new_path = suffixed(new_path, &targets);
+                targets.insert(new_path.clone());
+                renames.insert(old_path.clone(), new_path);
+            }
+        }
+        _ => (),
+    }
+
+    Ok(conflicts)
+}
+
+fn suffixed(path: &Path, targets: &HashSet<PathBuf>) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default();
+    let mut n = 1;
+    loop {
+        let mut file_name = OsString::from(stem);
+        file_name.push(format!(" ({})", n));
+        if let Some(ext) = path.extension() {
+            file_name.push(".");
+            file_name.push(ext);
+        }
+
+        let candidate = path.with_file_name(file_name);
+        if !targets.contains(&candidate) && fs::symlink_metadata(&candidate).is_err() {
+            return candidate;
+        }
+
+        n += 1;
+    }
+}
@@ -0,0 +1,65 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use clap::Args;
+
+/// Options of #count and #renamed_count
+#[derive(Args)]
+pub struct CounterArgs {
+    /// First value of the counters
+    #[clap(long, default_value_t = 0, allow_negative_numbers = true)]
+    pub count_start: i64,
+
+    /// Value added to the counters after each file
+    #[clap(long, default_value_t = 1, allow_negative_numbers = true)]
+    pub count_step: i64,
+
+    /// Pad the counters with zeros to this width
+    #[clap(long, default_value_t = 0)]
+    pub count_width: usize,
+
+    /// Restart the counters in every directory (used with -r)
+    #[clap(long)]
+    pub count_per_dir: bool,
+}
+
+/// Counter shared by the files, or by the files of a directory with --count-per-dir
+pub struct Counter<'a> {
+    args: &'a CounterArgs,
+    values: HashMap<PathBuf, i64>,
+}
+
+impl<'a> Counter<'a> {
+    pub fn new(args: &'a CounterArgs) -> Self {
+        Self {
+            args,
+            values: HashMap::new(),
+        }
+    }
+
+    /// Returns the formatted value for a file of dir
+    pub fn get(&self, dir: &Path) -> String {
+        let value = self
+            .values
+            .get(self.key(dir))
+            .copied()
+            .unwrap_or(self.args.count_start);
+        format!("{:0width$}", value, width = self.args.count_width)
+    }
+
+    pub fn advance(&mut self, dir: &Path) {
+        let key = self.key(dir).to_owned();
+        let value = self.values.entry(key).or_insert(self.args.count_start);
+        *value += self.args.count_step;
+    }
+
+    fn key<'p>(&self, dir: &'p Path) -> &'p Path {
+        if self.args.count_per_dir {
+            dir
+        } else {
+            Path::new("")
+        }
+    }
+}
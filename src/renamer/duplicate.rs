@@ -0,0 +1,79 @@
+use std::{
+    fs::{self, File},
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+};
+
+use indexmap::{IndexMap, IndexSet};
+
+/// Files renamed to the same target
+#[derive(Default)]
+pub struct Duplicates {
+    /// Files with the same content as the file kept for their target, they are not renamed
+    pub identical: IndexMap<PathBuf, PathBuf>,
+    /// Targets shared by files with a different content
+    pub conflicting: IndexSet<PathBuf>,
+}
+
+/// Finds the files renamed to the same target
+/// The file kept is the one already named like the target, or else the first one
+pub fn find(renames: &IndexMap<PathBuf, PathBuf>) -> Duplicates {
+    let mut by_target: IndexMap<&PathBuf, Vec<&PathBuf>> = IndexMap::new();
+    for (old_path, new_path) in renames.iter() {
+        by_target.entry(new_path).or_default().push(old_path);
+    }
+
+    let mut duplicates = Duplicates::default();
+    for (new_path, old_paths) in by_target.into_iter().filter(|(_, o)| o.len() > 1) {
+        let kept = old_paths
+            .iter()
+            .find(|o| **o == new_path)
+            .unwrap_or(&old_paths[0]);
+        for old_path in old_paths.iter().filter(|o| *o != kept) {
+            if same_content(kept, old_path) {
+                duplicates
+                    .identical
+                    .insert(old_path.to_path_buf(), kept.to_path_buf());
+            } else {
+                duplicates.conflicting.insert(new_path.clone());
+            }
+        }
+    }
+
+    duplicates
+}
+
+/// Whether both are files with the same content, compared byte by byte
+fn same_content(a: &Path, b: &Path) -> bool {
+    let (Ok(a_metadata), Ok(b_metadata)) = (fs::metadata(a), fs::metadata(b)) else {
+        return false;
+    };
+
+    if !a_metadata.is_file() || !b_metadata.is_file() || a_metadata.len() != b_metadata.len() {
+        return false;
+    }
+
+    let (Ok(a_file), Ok(b_file)) = (File::open(a), File::open(b)) else {
+        return false;
+    };
+
+    let mut a_reader = BufReader::new(a_file);
+    let mut b_reader = BufReader::new(b_file);
+    loop {
+        let (Ok(a_buf), Ok(b_buf)) = (a_reader.fill_buf(), b_reader.fill_buf()) else {
+            return false;
+        };
+
+        if a_buf.is_empty() || b_buf.is_empty() {
+            return a_buf.is_empty() && b_buf.is_empty();
+        }
+
+        let n = a_buf.len().min(b_buf.len());
+        if a_buf[..n] != b_buf[..n] {
+            return false;
+        }
+
+        a_reader.consume(n);
+        b_reader.consume(n);
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    process::{self, Command},
+};
+
+use indexmap::IndexMap;
+
+use crate::{
+    errors::{BasicError, Error},
+    utils::path_utils,
+};
+
+const HEADER: &str = "\
+# Edit the new names, paths are relative to the base directory
+# Delete a line to skip its file, lines starting with # are ignored
+";
+
+/// Lets the user edit the new names in $VISUAL or $EDITOR
+/// Returns the renames kept, the deleted lines and the names left as the old name are skipped
+pub fn edit(
+    base: &Path,
+    renames: &IndexMap<PathBuf, PathBuf>,
+) -> Result<IndexMap<PathBuf, PathBuf>, Box<dyn Error>> {
+    let (tmp_path, file) = create_temp()?;
+    let result = write_list(file, &tmp_path, base, renames)
+        .and_then(|_| open_editor(&tmp_path))
+        .and_then(|_| {
+            fs::read_to_string(&tmp_path).map_err(|e| {
+                BasicError::new(format!("Couldn't read {}: {}", tmp_path.display(), e)).into()
+            })
+        });
+    let _ = fs::remove_file(&tmp_path);
+
+    parse_list(&result?, base, renames)
+}
+
+/// Creates a new file in the temporary directory
+/// An existing file is never opened, it could be a link planted by another user
+fn create_temp() -> Result<(PathBuf, File), Box<dyn Error>> {
+    let mut n = 0;
+    loop {
+        let tmp_path = env::temp_dir().join(format!("nora-{}-{}.txt", process::id(), n));
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&tmp_path)
+        {
+            Ok(file) => return Ok((tmp_path, file)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => n += 1,
+            Err(e) => {
+                return Err(BasicError::new(format!(
+                    "Couldn't create {}: {}",
+                    tmp_path.display(),
+                    e
+                )))
+            }
+        }
+    }
+}
+
+/// Each line is preceded by a comment with the old name of its file
+fn write_list(
+    mut file: File,
+    tmp_path: &Path,
+    base: &Path,
+    renames: &IndexMap<PathBuf, PathBuf>,
+) -> Result<(), Box<dyn Error>> {
+    let mut content = String::from(HEADER);
+    for (i, (old_path, new_path)) in renames.iter().enumerate() {
+        let old_name = old_path.strip_prefix(base).unwrap_or(old_path);
+        let new_name = new_path.strip_prefix(base).unwrap_or(new_path);
+        content += &format!("# old: {}\n", old_name.display());
+        content += &format!("{}\t{}\n", i + 1, new_name.display());
+    }
+
+    file.write_all(content.as_bytes())
+        .map_err(|e| BasicError::new(format!("Couldn't write {}: {}", tmp_path.display(), e)))?;
+
+    Ok(())
+}
+
+fn open_editor(tmp_path: &Path) -> Result<(), Box<dyn Error>> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_owned());
+    let mut parts = editor.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| BasicError::new("The editor is empty".to_owned()))?;
+
+    let status = Command::new(program)
+        .args(parts)
+        .arg(tmp_path)
+        .status()
+        .map_err(|e| BasicError::new(format!("Couldn't run editor {}: {}", editor, e)))?;
+
+    if !status.success() {
+        return Err(BasicError::new(format!(
+            "Editor {} exited with {}",
+            editor, status
+        )));
+    }
+
+    Ok(())
+}
+
+fn parse_list(
+    content: &str,
+    base: &Path,
+    renames: &IndexMap<PathBuf, PathBuf>,
+) -> Result<IndexMap<PathBuf, PathBuf>, Box<dyn Error>> {
+    let mut edited = IndexMap::new();
+    for line in content.lines() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let invalid = || BasicError::new(format!("Invalid line: {}", line));
+        let (n, new_name) = line.split_once('\t').ok_or_else(invalid)?;
+        let (old_path, _) = n
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| renames.get_index(n.checked_sub(1)?))
+            .ok_or_else(invalid)?;
+
+        let new_name = new_name.trim();
+        if new_name.is_empty() {
+            continue;
+        }
+
+        let new_path = path_utils::normalize(&base.join(new_name));
+        if !new_path.starts_with(base) {
+            return Err(BasicError::new(format!(
+                "{} is outside of the base directory",
+                new_path.display()
+            )));
+        }
+
+        if &new_path == old_path {
+            continue;
+        }
+
+        if edited.insert(old_path.clone(), new_path).is_some() {
+            return Err(BasicError::new(format!(
+                "Line {} is there more than once",
+                n.trim()
+            )));
+        }
+    }
+
+    Ok(edited)
+}
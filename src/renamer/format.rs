@@ -0,0 +1,127 @@
+use std::io::{self, BufRead, Write};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{BasicError, Error};
+
+/// Machine readable formats for the rename plan
+#[derive(Clone, Copy, ValueEnum)]
+pub enum PlanFormat {
+    /// One JSON object per line
+    Json,
+    /// Comma separated values with a header
+    Csv,
+    /// Tab separated values with a header
+    Tsv,
+    /// Source and target separated by NUL, only for the files to rename
+    Null,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    #[default]
+    Rename,
+    Unchanged,
+    Conflict,
+    /// Same target and content as another file, not renamed
+    Identical,
+    Error,
+}
+
+impl Status {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Status::Rename => "rename",
+            Status::Unchanged => "unchanged",
+            Status::Conflict => "conflict",
+            Status::Identical => "identical",
+            Status::Error => "error",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PlanRecord {
+    pub source: String,
+    pub target: Option<String>,
+    #[serde(default)]
+    pub status: Status,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+pub fn write_plan(
+    out: &mut impl Write,
+    records: &[PlanRecord],
+    format: PlanFormat,
+) -> io::Result<()> {
+    match format {
+        PlanFormat::Json => {
+            for record in records {
+                serde_json::to_writer(&mut *out, record)?;
+                writeln!(out)?;
+            }
+        }
+        PlanFormat::Csv | PlanFormat::Tsv => {
+            let (sep, escape): (&str, fn(&str) -> String) = match format {
+                PlanFormat::Csv => (",", escape_csv),
+                _ => ("\t", escape_tsv),
+            };
+
+            writeln!(out, "{}", ["source", "target", "status", "error"].join(sep))?;
+            for record in records {
+                let fields = [
+                    escape(&record.source),
+                    escape(record.target.as_deref().unwrap_or_default()),
+                    record.status.as_str().to_owned(),
+                    escape(record.error.as_deref().unwrap_or_default()),
+                ];
+                writeln!(out, "{}", fields.join(sep))?;
+            }
+        }
+        PlanFormat::Null => {
+            for record in records.iter().filter(|r| r.status == Status::Rename) {
+                let target = record.target.as_deref().unwrap_or_default();
+                write!(out, "{}\0{}\0", record.source, target)?;
+            }
+        }
+    }
+
+    out.flush()
+}
+
+/// Reads a plan written with the json format
+/// Records without a target (errors) are kept, the status of the others is not checked
+pub fn read_plan(reader: impl BufRead) -> Result<Vec<PlanRecord>, Box<dyn Error>> {
+    let mut records = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| BasicError::new(format!("Couldn't read plan: {}", e)))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record = serde_json::from_str(&line)
+            .map_err(|e| BasicError::new(format!("Invalid plan on line {}: {}", i + 1, e)))?;
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn escape_tsv(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
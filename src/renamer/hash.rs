@@ -0,0 +1,76 @@
+use std::{fs::File, io::Read, path::Path};
+
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+use crate::errors::{BasicError, Error};
+
+const BUFFER_SIZE: usize = 64 * 1024;
+
+enum Hasher {
+    Sha256(Sha256),
+    Md5(Md5),
+    Crc32(crc32fast::Hasher),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    fn new(algorithm: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(match algorithm {
+            "sha256" => Hasher::Sha256(Sha256::new()),
+            "md5" => Hasher::Md5(Md5::new()),
+            "crc32" => Hasher::Crc32(crc32fast::Hasher::new()),
+            "blake3" => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+            _ => {
+                return Err(BasicError::new(format!(
+                    "Unknown hash algorithm {}, expected sha256, md5, crc32 or blake3",
+                    algorithm
+                )))
+            }
+        })
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha256(h) => h.update(data),
+            Hasher::Md5(h) => h.update(data),
+            Hasher::Crc32(h) => h.update(data),
+            Hasher::Blake3(h) => {
+                h.update(data);
+            }
+        }
+    }
+
+    fn finalize(self) -> String {
+        match self {
+            Hasher::Sha256(h) => to_hex(&h.finalize()),
+            Hasher::Md5(h) => to_hex(&h.finalize()),
+            Hasher::Crc32(h) => format!("{:08x}", h.finalize()),
+            Hasher::Blake3(h) => h.finalize().to_hex().to_string(),
+        }
+    }
+}
+
+/// Returns the hash of the content of the file in lowercase hex
+/// The file is read in chunks, so large files are not loaded in memory
+pub fn hash_file(path: &Path, algorithm: &str) -> Result<String, Box<dyn Error>> {
+    let mut hasher = Hasher::new(algorithm)?;
+    let read_error = |e| BasicError::new(format!("Couldn't read {}: {}", path.display(), e));
+    let mut file = File::open(path).map_err(read_error)?;
+
+    let mut buffer = vec![0; BUFFER_SIZE];
+    loop {
+        let read = file.read(&mut buffer).map_err(read_error)?;
+        if read == 0 {
+            break;
+        }
+
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher.finalize())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,97 @@
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use indexmap::IndexMap;
+
+use crate::{
+    errors::{BasicError, Error},
+    utils::path_utils,
+};
+
+const HELP: &str = "\
+y - rename this file
+n - don't rename this file
+e - edit the new name of this file
+a - rename this file and all the remaining ones
+q - don't rename this file nor the remaining ones
+? - print help";
+
+/// Asks the user for every rename
+/// Returns the renames accepted, with their edited names
+pub fn ask(
+    base: &Path,
+    renames: &IndexMap<PathBuf, PathBuf>,
+) -> Result<IndexMap<PathBuf, PathBuf>, Box<dyn Error>> {
+    let mut accepted = IndexMap::new();
+    let mut all = false;
+    for (old_path, new_path) in renames.iter() {
+        if old_path == new_path {
+            continue;
+        }
+
+        if all {
+            accepted.insert(old_path.clone(), new_path.clone());
+            continue;
+        }
+
+        let conflict = !renames.contains_key(new_path) && path_utils::is_taken(old_path, new_path);
+        loop {
+            let answer = read_answer(&format!(
+                "{} -> {}{} [y,n,e,a,q,?] ",
+                path_utils::display(base, old_path),
+                path_utils::display(base, new_path),
+                if conflict { " (conflict)" } else { "" }
+            ))?;
+
+            match answer.as_str() {
+                "y" => {
+                    accepted.insert(old_path.clone(), new_path.clone());
+                }
+                "n" => (),
+                "e" => {
+                    let new_name = read_answer("New name: ")?;
+                    if new_name.is_empty() {
+                        continue;
+                    }
+
+                    let new_path = path_utils::normalize(&base.join(new_name));
+                    if !new_path.starts_with(base) {
+                        println!("{} is outside of the base directory", new_path.display());
+                        continue;
+                    }
+
+                    accepted.insert(old_path.clone(), new_path);
+                }
+                "a" => {
+                    accepted.insert(old_path.clone(), new_path.clone());
+                    all = true;
+                }
+                "q" => return Ok(accepted),
+                _ => {
+                    println!("{}", HELP);
+                    continue;
+                }
+            }
+
+            break;
+        }
+    }
+
+    Ok(accepted)
+}
+
+fn read_answer(question: &str) -> Result<String, Box<dyn Error>> {
+    print!("{}", question);
+    let mut a = String::new();
+    let read = io::stdout()
+        .flush()
+        .and_then(|_| io::stdin().read_line(&mut a))
+        .map_err(|e| BasicError::new(format!("Failed to read input: {}", e)))?;
+    if read == 0 {
+        return Err(BasicError::new("Unexpected end of input".to_owned()));
+    }
+
+    Ok(a.trim().to_owned())
+}
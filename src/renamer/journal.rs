@@ -0,0 +1,257 @@
+use std::{
+    collections::HashSet,
+    env,
+    fs::{self, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use chrono::DateTime;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::{BasicError, Error},
+    utils::time_utils,
+};
+
+/// One applied rename, with a fingerprint of the renamed file
+/// used to detect changes before undoing it
+#[derive(Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub old_path: PathBuf,
+    pub new_path: PathBuf,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    #[serde(default)]
+    pub is_dir: bool,
+}
+
+impl JournalEntry {
+    fn new(old_path: &Path, new_path: &Path) -> Self {
+        let metadata = fs::symlink_metadata(new_path).ok();
+        Self {
+            old_path: old_path.to_owned(),
+            new_path: new_path.to_owned(),
+            size: metadata.as_ref().map_or(0, |m| m.len()),
+            modified: metadata.as_ref().and_then(|m| m.modified().ok()),
+            is_dir: metadata.is_some_and(|m| m.is_dir()),
+        }
+    }
+
+    /// Returns why this entry cannot be undone, if it can't
+    /// taken_paths are the new paths of the batch, freed when it's undone
+    fn check(&self, taken_paths: &HashSet<&PathBuf>) -> Option<String> {
+        let Ok(metadata) = fs::symlink_metadata(&self.new_path) else {
+            return Some(format!("{} no longer exists", self.new_path.display()));
+        };
+
+        // Renaming what's inside a directory changes its size and mtime
+        let changed = if self.is_dir || metadata.is_dir() {
+            self.is_dir != metadata.is_dir()
+        } else {
+            metadata.len() != self.size || metadata.modified().ok() != self.modified
+        };
+        if changed {
+            return Some(format!(
+                "{} changed since the batch ran",
+                self.new_path.display()
+            ));
+        }
+
+        if fs::symlink_metadata(&self.old_path).is_ok() && !taken_paths.contains(&self.old_path) {
+            return Some(format!("{} already exists", self.old_path.display()));
+        }
+
+        None
+    }
+}
+
+/// A batch of renames applied by a single run of nora
+#[derive(Serialize, Deserialize)]
+pub struct Batch {
+    #[serde(skip)]
+    pub id: String,
+    pub timestamp: u64,
+    pub pattern: String,
+    pub template: String,
+    pub entries: Vec<JournalEntry>,
+    /// Directories created for the targets, the top most first
+    #[serde(default)]
+    pub created_dirs: Vec<PathBuf>,
+    #[serde(default)]
+    pub undone: bool,
+}
+
+impl Batch {
+    /// Returns why this batch cannot be undone, empty if it can
+    pub fn check(&self) -> Vec<String> {
+        let taken_paths: HashSet<&PathBuf> = self.entries.iter().map(|e| &e.new_path).collect();
+        self.entries
+            .iter()
+            .filter_map(|e| e.check(&taken_paths))
+            .collect()
+    }
+
+    /// Removes the directories created by the batch, the ones that are no longer empty are kept
+    /// Returns the directories that couldn't be removed
+    pub fn remove_created_dirs(&self) -> Vec<String> {
+        let mut kept = Vec::new();
+        for dir in self.created_dirs.iter().rev() {
+            if let Err(e) = fs::remove_dir(dir) {
+                kept.push(format!("Couldn't remove {}: {}", dir.display(), e));
+            }
+        }
+
+        kept
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "{}  {}  {} files  '{}' -> '{}'{}",
+            self.id,
+            DateTime::from_timestamp(self.timestamp as i64, 0).map_or(String::new(), |d| d
+                .format("%Y-%m-%d %H:%M:%S UTC")
+                .to_string()),
+            self.entries.len(),
+            self.pattern,
+            self.template,
+            if self.undone { "  (undone)" } else { "" }
+        )
+    }
+}
+
+pub struct Journal {
+    dir: PathBuf,
+}
+
+impl Journal {
+    /// Opens the journal in the state directory
+    /// NORA_STATE_DIR, then XDG_STATE_HOME/nora, then ~/.local/state/nora
+    pub fn open() -> Result<Self, Box<dyn Error>> {
+        let state_dir = if let Some(dir) = env::var_os("NORA_STATE_DIR") {
+            PathBuf::from(dir)
+        } else if let Some(dir) = env::var_os("XDG_STATE_HOME") {
+            PathBuf::from(dir).join("nora")
+        } else if let Some(dir) = env::var_os("HOME") {
+            PathBuf::from(dir).join(".local").join("state").join("nora")
+        } else if let Some(dir) = env::var_os("LOCALAPPDATA") {
+            PathBuf::from(dir).join("nora")
+        } else {
+            return Err(BasicError::new(
+                "Couldn't find a state directory for the journal, set NORA_STATE_DIR".to_owned(),
+            ));
+        };
+
+        let dir = state_dir.join("journal");
+        fs::create_dir_all(&dir).map_err(|e| {
+            BasicError::new(format!(
+                "Couldn't create journal directory {}: {}",
+                dir.display(),
+                e
+            ))
+        })?;
+
+        Ok(Self { dir })
+    }
+
+    pub fn record(
+        &self,
+        pattern: &str,
+        template: &str,
+        renames: &IndexMap<PathBuf, PathBuf>,
+        created_dirs: &[PathBuf],
+    ) -> Result<Batch, Box<dyn Error>> {
+        let timestamp = time_utils::now_unix();
+        let mut id = timestamp.to_string();
+        let mut n = 1;
+        while self.batch_path(&id).exists() {
+            id = format!("{}-{}", timestamp, n);
+            n += 1;
+        }
+
+        let batch = Batch {
+            id,
+            timestamp,
+            pattern: pattern.to_owned(),
+            template: template.to_owned(),
+            entries: renames
+                .iter()
+                .map(|(old_path, new_path)| JournalEntry::new(old_path, new_path))
+                .collect(),
+            created_dirs: created_dirs.to_vec(),
+            undone: false,
+        };
+
+        self.save(&batch)?;
+        Ok(batch)
+    }
+
+    pub fn save(&self, batch: &Batch) -> Result<(), Box<dyn Error>> {
+        let path = self.batch_path(&batch.id);
+        let file = File::create(&path).map_err(|e| {
+            BasicError::new(format!("Couldn't write journal {}: {}", path.display(), e))
+        })?;
+
+        serde_json::to_writer_pretty(file, batch).map_err(|e| {
+            BasicError::new(format!("Couldn't write journal {}: {}", path.display(), e))
+        })?;
+
+        Ok(())
+    }
+
+    /// Returns every batch, oldest first
+    pub fn batches(&self) -> Result<Vec<Batch>, Box<dyn Error>> {
+        let read_dir = fs::read_dir(&self.dir).map_err(|e| {
+            BasicError::new(format!(
+                "Couldn't read journal directory {}: {}",
+                self.dir.display(),
+                e
+            ))
+        })?;
+
+        let mut batches = Vec::new();
+        for entry in read_dir.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                batches.push(self.load(&path)?);
+            }
+        }
+
+        batches.sort_by(|a, b| (a.timestamp, &a.id).cmp(&(b.timestamp, &b.id)));
+        Ok(batches)
+    }
+
+    /// Returns the batch with this id, or the latest batch not undone yet
+    pub fn get(&self, id: Option<&str>) -> Result<Batch, Box<dyn Error>> {
+        let batches = self.batches()?;
+        let batch = match id {
+            Some(id) => batches.into_iter().find(|b| b.id == id),
+            None => batches.into_iter().rev().find(|b| !b.undone),
+        };
+
+        batch.ok_or_else(|| match id {
+            Some(id) => BasicError::new(format!("Couldn't find batch: {}", id)).into(),
+            None => BasicError::new("No batch to undo".to_owned()).into(),
+        })
+    }
+
+    fn load(&self, path: &Path) -> Result<Batch, Box<dyn Error>> {
+        let file = File::open(path).map_err(|e| {
+            BasicError::new(format!("Couldn't read journal {}: {}", path.display(), e))
+        })?;
+
+        let mut batch: Batch = serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| BasicError::new(format!("Invalid journal {}: {}", path.display(), e)))?;
+        batch.id = path
+            .file_stem()
+            .map_or(String::new(), |s| s.to_string_lossy().into_owned());
+
+        Ok(batch)
+    }
+
+    fn batch_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+}
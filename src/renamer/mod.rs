@@ -0,0 +1,13 @@
+pub mod conflict;
+pub mod counter;
+pub mod duplicate;
+pub mod editor;
+pub mod format;
+pub mod hash;
+pub mod interactive;
+pub mod journal;
+pub mod planner;
+pub mod tags;
+pub mod transaction;
+pub mod vars;
+pub mod walk;
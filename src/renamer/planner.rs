@@ -0,0 +1,336 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use indexmap::IndexMap;
+
+use crate::errors::{BasicError, Error};
+
+/// Orders the renames so that
+/// - no target is still taken by another source of the batch when it's renamed to
+/// - everything inside a renamed directory is renamed before the directory
+///
+/// Targets are paths before any rename of the batch is applied
+/// Cycles (swaps, rotations) are broken by moving one file to a temporary name
+/// Renames where the source is the target are dropped
+pub fn plan(
+    renames: &IndexMap<PathBuf, PathBuf>,
+) -> Result<Vec<(PathBuf, PathBuf)>, Box<dyn Error>> {
+    let mut nodes: Vec<(PathBuf, PathBuf)> = renames
+        .iter()
+        .filter(|(old_path, new_path)| old_path != new_path)
+        .map(|(old_path, new_path)| (old_path.clone(), new_path.clone()))
+        .collect();
+    let by_source: HashMap<&PathBuf, usize> =
+        nodes.iter().enumerate().map(|(i, (s, _))| (s, i)).collect();
+
+    // waits_on[i] are the renames to do before i
+    // chain_wait[i] is the rename whose source is the target of i
+    let mut waits_on: Vec<HashSet<usize>> = vec![HashSet::new(); nodes.len()];
+    let mut chain_wait: Vec<Option<usize>> = vec![None; nodes.len()];
+    for (i, (old_path, new_path)) in nodes.iter().enumerate() {
+        if let Some(&j) = by_source.get(new_path) {
+            waits_on[i].insert(j);
+            chain_wait[i] = Some(j);
+        }
+
+        for path in [old_path, new_path] {
+            for dir in path.ancestors().skip(1) {
+                if let Some(&j) = by_source.get(&dir.to_path_buf()) {
+                    waits_on[j].insert(i);
+                }
+            }
+        }
+    }
+
+    let mut waiters: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+    for (i, waits) in waits_on.iter().enumerate() {
+        for &j in waits {
+            waiters[j].push(i);
+        }
+    }
+
+    let mut ready: VecDeque<usize> = (0..nodes.len())
+        .filter(|&i| waits_on[i].is_empty())
+        .collect();
+    let mut done = vec![false; nodes.len()];
+    let mut steps = Vec::new();
+    while !done.iter().all(|d| *d) {
+        while let Some(j) = ready.pop_front() {
+            done[j] = true;
+            steps.push(nodes[j].clone());
+            for &i in &waiters[j] {
+                if waits_on[i].remove(&j) && waits_on[i].is_empty() {
+                    ready.push_back(i);
+                }
+            }
+        }
+
+        if done.iter().all(|d| *d) {
+            break;
+        }
+
+        // Everything left waits on a cycle, one of the renames is moved to a temporary name
+        // It must not contain renames left, otherwise their paths would change
+        // Freeing it may not make anything ready yet, then the next cycle is broken
+        let j = (0..nodes.len())
+            .filter(|&j| !done[j])
+            .find(|&j| {
+                waits_on[j].iter().all(|&k| chain_wait[j] == Some(k))
+                    && chain_wait.contains(&Some(j))
+            })
+            .ok_or_else(|| BasicError::new("Couldn't find an order to rename files".to_owned()))?;
+
+        let tmp_path = temp_path(&nodes[j].0);
+        steps.push((nodes[j].0.clone(), tmp_path.clone()));
+        nodes[j].0 = tmp_path;
+        for i in 0..nodes.len() {
+            if chain_wait[i] == Some(j) {
+                chain_wait[i] = None;
+                if waits_on[i].remove(&j) && waits_on[i].is_empty() {
+                    ready.push_back(i);
+                }
+            }
+        }
+    }
+
+    Ok(steps)
+}
+
+/// Turns the targets into the paths they have once every rename of the batch is applied
+/// A target inside a renamed directory moves with it
+pub fn finalize(renames: &IndexMap<PathBuf, PathBuf>) -> IndexMap<PathBuf, PathBuf> {
+    renames
+        .iter()
+        .map(|(old_path, new_path)| (old_path.clone(), final_path(renames, new_path)))
+        .collect()
+}
+
+/// Path once every rename of the batch is applied
+pub fn final_path(renames: &IndexMap<PathBuf, PathBuf>, path: &Path) -> PathBuf {
+    path.ancestors()
+        .skip(1)
+        .find_map(|dir| {
+            let new_dir = renames.get(dir)?;
+            Some(final_path(renames, new_dir).join(path.strip_prefix(dir).ok()?))
+        })
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Opposite of finalize, turns the targets into the paths they have before the batch is applied
+pub fn unfinalize(renames: &IndexMap<PathBuf, PathBuf>) -> IndexMap<PathBuf, PathBuf> {
+    let by_target: HashMap<&Path, &PathBuf> = renames
+        .iter()
+        .map(|(old_path, new_path)| (new_path.as_path(), old_path))
+        .collect();
+
+    renames
+        .iter()
+        .map(|(old_path, new_path)| {
+            let new_path = new_path
+                .ancestors()
+                .skip(1)
+                .find_map(|dir| Some(by_target.get(dir)?.join(new_path.strip_prefix(dir).ok()?)))
+                .unwrap_or_else(|| new_path.clone());
+            (old_path.clone(), new_path)
+        })
+        .collect()
+}
+
+static TEMP_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns a free temporary path in the same directory as path
+pub fn temp_path(path: &Path) -> PathBuf {
+    loop {
+        let n = TEMP_COUNT.fetch_add(1, Ordering::Relaxed);
+        let tmp_path = path.with_file_name(format!(".nora-{}-{}.tmp", process::id(), n));
+        if fs::symlink_metadata(&tmp_path).is_err() {
+            return tmp_path;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// Applies the steps to a tree of paths to contents
+    /// Renaming a directory moves everything inside it
+    fn apply(tree: &[(&str, &str)], steps: &[(PathBuf, PathBuf)]) -> HashMap<PathBuf, String> {
+        let mut tree: HashMap<PathBuf, String> = tree
+            .iter()
+            .map(|(path, content)| (PathBuf::from(path), content.to_string()))
+            .collect();
+        for (old_path, new_path) in steps {
+            assert!(tree.contains_key(old_path), "{:?} doesn't exist", old_path);
+            assert!(
+                !tree.contains_key(new_path),
+                "{:?} already exists",
+                new_path
+            );
+            tree = tree
+                .into_iter()
+                .map(|(path, content)| match path.strip_prefix(old_path) {
+                    Ok(rest) => (new_path.join(rest), content),
+                    Err(_) => (path, content),
+                })
+                .map(|(path, content)| (path.components().collect(), content))
+                .collect();
+        }
+
+        tree
+    }
+
+    fn renames(pairs: &[(&str, &str)]) -> IndexMap<PathBuf, PathBuf> {
+        pairs
+            .iter()
+            .map(|(old_path, new_path)| (PathBuf::from(old_path), PathBuf::from(new_path)))
+            .collect()
+    }
+
+    fn expected(tree: &[(&str, &str)]) -> HashMap<PathBuf, String> {
+        tree.iter()
+            .map(|(path, content)| (PathBuf::from(path), content.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn chain() {
+        let tree = [("/p/a", "a"), ("/p/b", "b")];
+        let steps = plan(&renames(&[("/p/a", "/p/b"), ("/p/b", "/p/c")])).unwrap();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(
+            apply(&tree, &steps),
+            expected(&[("/p/b", "a"), ("/p/c", "b")])
+        );
+    }
+
+    #[test]
+    fn several_cycles() {
+        let tree = [
+            ("/p/a", "a"),
+            ("/p/b", "b"),
+            ("/p/c", "c"),
+            ("/p/d", "d"),
+            ("/p/e", "e"),
+            ("/p/f", "f"),
+            ("/p/g", "g"),
+        ];
+        let steps = plan(&renames(&[
+            ("/p/a", "/p/b"),
+            ("/p/b", "/p/a"),
+            ("/p/c", "/p/d"),
+            ("/p/d", "/p/e"),
+            ("/p/e", "/p/c"),
+            ("/p/f", "/p/g"),
+            ("/p/g", "/p/f"),
+        ]))
+        .unwrap();
+        assert_eq!(
+            apply(&tree, &steps),
+            expected(&[
+                ("/p/b", "a"),
+                ("/p/a", "b"),
+                ("/p/d", "c"),
+                ("/p/e", "d"),
+                ("/p/c", "e"),
+                ("/p/g", "f"),
+                ("/p/f", "g"),
+            ])
+        );
+    }
+
+    #[test]
+    fn directory_cycle_with_contents_in_a_cycle() {
+        let tree = [
+            ("/p/x", "x"),
+            ("/p/x/a", "a"),
+            ("/p/x/b", "b"),
+            ("/p/y", "y"),
+            ("/p/y/c", "c"),
+            ("/p/y/d", "d"),
+            ("/p/f", "f"),
+            ("/p/g", "g"),
+        ];
+        let steps = plan(&renames(&[
+            ("/p/f", "/p/g"),
+            ("/p/g", "/p/f"),
+            ("/p/x/a", "/p/x/b"),
+            ("/p/x/b", "/p/x/a"),
+            ("/p/y/c", "/p/y/d"),
+            ("/p/y/d", "/p/y/c"),
+            ("/p/x", "/p/y"),
+            ("/p/y", "/p/x"),
+        ]))
+        .unwrap();
+        assert_eq!(
+            apply(&tree, &steps),
+            expected(&[
+                ("/p/y", "x"),
+                ("/p/y/b", "a"),
+                ("/p/y/a", "b"),
+                ("/p/x", "y"),
+                ("/p/x/d", "c"),
+                ("/p/x/c", "d"),
+                ("/p/g", "f"),
+                ("/p/f", "g"),
+            ])
+        );
+    }
+
+    #[test]
+    fn swaps_before_a_directory_cycle() {
+        let tree = [
+            ("/p/a", "a"),
+            ("/p/b", "b"),
+            ("/p/c", "c"),
+            ("/p/d", "d"),
+            ("/p/e", "e"),
+            ("/p/f", "f"),
+            ("/p/x", "x"),
+            ("/p/x/g", "g"),
+            ("/p/x/h", "h"),
+            ("/p/y", "y"),
+        ];
+        let steps = plan(&renames(&[
+            ("/p/f", "/p/e"),
+            ("/p/e", "/p/f"),
+            ("/p/d", "/p/c"),
+            ("/p/c", "/p/d"),
+            ("/p/b", "/p/a"),
+            ("/p/a", "/p/b"),
+            ("/p/y", "/p/x"),
+            ("/p/x", "/p/y"),
+            ("/p/x/h", "/p/x/g"),
+            ("/p/x/g", "/p/x/h"),
+        ]))
+        .unwrap();
+        assert_eq!(
+            apply(&tree, &steps),
+            expected(&[
+                ("/p/b", "a"),
+                ("/p/a", "b"),
+                ("/p/d", "c"),
+                ("/p/c", "d"),
+                ("/p/f", "e"),
+                ("/p/e", "f"),
+                ("/p/y", "x"),
+                ("/p/y/h", "g"),
+                ("/p/y/g", "h"),
+                ("/p/x", "y"),
+            ])
+        );
+    }
+
+    #[test]
+    fn identical_renames_are_dropped() {
+        let steps = plan(&renames(&[("/p/a", "/p/a")])).unwrap();
+        assert!(steps.is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+use exif::{Exif, Field, In, Reader, Tag, Value};
+use lofty::{
+    config::ParseOptions,
+    prelude::{Accessor, ItemKey, TaggedFileExt},
+    probe::Probe,
+};
+
+use crate::{
+    ast::interpreter::LazyVars,
+    errors::{BasicError, Error},
+};
+
+use super::hash;
+
+/// Tags and hashes read from the content of a file, only when a template uses them
+/// Tags missing from the file are empty
+pub struct FileTags {
+    path: PathBuf,
+    hashes: HashMap<String, String>,
+    exif: Option<Option<Exif>>,
+    audio: Option<Option<lofty::tag::Tag>>,
+}
+
+impl FileTags {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_owned(),
+            hashes: HashMap::new(),
+            exif: None,
+            audio: None,
+        }
+    }
+
+    fn exif_tag(&mut self, name: &str) -> String {
+        let path = &self.path;
+        let Some(exif) = self.exif.get_or_insert_with(|| read_exif(path)) else {
+            return String::new();
+        };
+
+        // The primary image comes first, the thumbnail only has a few tags of its own
+        let field = exif
+            .fields()
+            .filter(|f| f.tag.to_string() == name)
+            .min_by_key(|f| f.ifd_num != In::PRIMARY);
+        match field {
+            Some(field) => display_field(exif, field),
+            None => String::new(),
+        }
+    }
+
+    fn audio_tag(&mut self, name: &str) -> Result<String, Box<dyn Error>> {
+        let path = &self.path;
+        let tag = self
+            .audio
+            .get_or_insert_with(|| read_audio_tag(path))
+            .as_ref();
+        let number = |n: Option<u32>| n.map_or(String::new(), |n| n.to_string());
+        let text = |t: Option<Cow<str>>| t.map_or(String::new(), |t| t.into_owned());
+        Ok(match name {
+            "track" => number(tag.and_then(|t| t.track())),
+            "track_total" => number(tag.and_then(|t| t.track_total())),
+            "disc" => number(tag.and_then(|t| t.disk())),
+            "title" => text(tag.and_then(|t| t.title())),
+            "artist" => text(tag.and_then(|t| t.artist())),
+            "album" => text(tag.and_then(|t| t.album())),
+            "album_artist" => text(
+                tag.and_then(|t| t.get_string(ItemKey::AlbumArtist))
+                    .map(Cow::Borrowed),
+            ),
+            "genre" => text(tag.and_then(|t| t.genre())),
+            "year" => number(tag.and_then(|t| t.date()).map(|d| d.year.into())),
+            _ => return Err(BasicError::new(format!("Unknown audio tag: {}", name))),
+        })
+    }
+}
+
+impl LazyVars for FileTags {
+    fn get(&mut self, name: &str) -> Result<Option<String>, Box<dyn Error>> {
+        if let Some(tag) = name.strip_prefix("#exif.") {
+            return Ok(Some(self.exif_tag(tag)));
+        }
+
+        if let Some(tag) = name.strip_prefix("#tag.") {
+            return self.audio_tag(tag).map(Some);
+        }
+
+        Ok(None)
+    }
+
+    fn hash(&mut self, algorithm: &str) -> Result<String, Box<dyn Error>> {
+        if let Some(hash) = self.hashes.get(algorithm) {
+            return Ok(hash.clone());
+        }
+
+        let hash = hash::hash_file(&self.path, algorithm)?;
+        self.hashes.insert(algorithm.to_owned(), hash.clone());
+        Ok(hash)
+    }
+}
+
+/// Files that can't be read or have no exif have no tags
+fn read_exif(path: &Path) -> Option<Exif> {
+    let file = File::open(path).ok()?;
+    Reader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .ok()
+}
+
+/// ID3v2, Vorbis comments, FLAC, APE and MP4 tags are read, the primary tag of the format first
+fn read_audio_tag(path: &Path) -> Option<lofty::tag::Tag> {
+    let file = Probe::open(path)
+        .ok()?
+        .options(ParseOptions::new().read_properties(false))
+        .read()
+        .ok()?;
+    file.primary_tag().or_else(|| file.first_tag()).cloned()
+}
+
+fn display_field(exif: &Exif, field: &Field) -> String {
+    match (&field.value, field.tag) {
+        // Dates are written like 2024-01-31 12:00:00, so they can be used with date()
+        (_, Tag::DateTime | Tag::DateTimeOriginal | Tag::DateTimeDigitized) => {
+            field.display_value().to_string()
+        }
+        (Value::Ascii(parts), _) => parts
+            .iter()
+            .map(|p| String::from_utf8_lossy(p).trim().to_owned())
+            .collect::<Vec<String>>()
+            .join(" "),
+        // Coordinates are in decimal degrees, negative in the south and west
+        (Value::Rational(dms), Tag::GPSLatitude | Tag::GPSLongitude) if dms.len() == 3 => {
+            let degrees = dms[0].to_f64() + dms[1].to_f64() / 60.0 + dms[2].to_f64() / 3600.0;
+            let reference = if field.tag == Tag::GPSLatitude {
+                Tag::GPSLatitudeRef
+            } else {
+                Tag::GPSLongitudeRef
+            };
+            let negative = exif
+                .get_field(reference, field.ifd_num)
+                .is_some_and(|r| matches!(display_field(exif, r).as_str(), "S" | "W"));
+            format!("{:.6}", if negative { -degrees } else { degrees })
+        }
+        _ => field.display_value().with_unit(exif).to_string(),
+    }
+}
@@ -0,0 +1,228 @@
+use std::{
+    collections::HashSet,
+    fs::{self, rename},
+    path::{Path, PathBuf},
+};
+
+use indexmap::{IndexMap, IndexSet};
+
+use crate::{
+    errors::{BasicError, Error},
+    utils::path_utils,
+};
+
+use super::{conflict, planner};
+
+#[derive(Clone, Copy, Default)]
+pub struct Options {
+    /// Replace the existing files outside the batch taking a target
+    pub overwrite: bool,
+    /// Create the missing directories of the targets
+    pub create_dirs: bool,
+}
+
+/// Checks that every rename of the batch can be applied
+/// Returns the problems found, empty if none
+pub fn preflight(renames: &IndexMap<PathBuf, PathBuf>, options: Options) -> Vec<String> {
+    let sources: HashSet<&PathBuf> = renames.keys().collect();
+    let mut dirs: IndexSet<PathBuf> = IndexSet::new();
+    let mut problems = Vec::new();
+    for (old_path, new_path) in renames.iter() {
+        if fs::symlink_metadata(old_path).is_err() {
+            problems.push(format!("{} doesn't exist", old_path.display()));
+        }
+
+        if new_path.starts_with(old_path) && new_path != old_path {
+            problems.push(format!(
+                "{} can't be moved inside itself",
+                old_path.display()
+            ));
+        }
+
+        // A case only rename finds its source on case insensitive filesystems
+        let taken = fs::symlink_metadata(new_path)
+            .ok()
+            .filter(|_| !path_utils::is_same_file(old_path, new_path));
+        if let Some(metadata) = taken {
+            if metadata.is_dir() && !sources.contains(new_path) {
+                problems.push(format!("{} is an existing directory", new_path.display()));
+            } else if !options.overwrite && !sources.contains(new_path) {
+                problems.push(format!("{} already exists", new_path.display()));
+            }
+        }
+
+        let missing = path_utils::missing_ancestors(new_path);
+        if let Some(top) = missing.first() {
+            if options.create_dirs {
+                dirs.extend(top.parent().map(Path::to_path_buf));
+            } else {
+                problems.push(format!(
+                    "{} doesn't exist",
+                    missing.last().unwrap_or(top).display()
+                ));
+            }
+        } else {
+            dirs.extend(new_path.parent().map(Path::to_path_buf));
+        }
+
+        dirs.extend(old_path.parent().map(Path::to_path_buf));
+    }
+
+    for dir in dirs {
+        if dir.exists() && !is_writable(&dir) {
+            problems.push(format!("{} is not writable", dir.display()));
+        }
+    }
+
+    problems
+}
+
+/// Applies the renames as a single transaction, in the order given by the planner
+/// If one of them fails, the renames already done are rolled back
+/// Returns the directories created for the targets, the top most first
+pub fn apply(
+    renames: &IndexMap<PathBuf, PathBuf>,
+    options: Options,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let problems = preflight(renames, options);
+    if !problems.is_empty() {
+        return Err(BasicError::new(format!(
+            "Cannot rename files:\n\t{}",
+            problems.join("\n\t")
+        )));
+    }
+
+    // Every rename must end with a step to its target, otherwise the batch would be half applied
+    let plan = planner::plan(renames)?;
+    let targets: HashSet<&PathBuf> = plan.iter().map(|(_, new_path)| new_path).collect();
+    if let Some((old_path, _)) = renames
+        .iter()
+        .find(|(old_path, new_path)| old_path != new_path && !targets.contains(new_path))
+    {
+        return Err(BasicError::new(format!(
+            "Couldn't find an order to rename {}",
+            old_path.display()
+        )));
+    }
+
+    let mut created = Vec::new();
+    if options.create_dirs {
+        for new_path in renames.values() {
+            for dir in path_utils::missing_ancestors(new_path) {
+                if let Err(e) = fs::create_dir(&dir) {
+                    let mut msg = format!("Couldn't create {}: {}", dir.display(), e);
+                    msg += &remove_created(&created);
+                    return Err(BasicError::new(msg));
+                }
+
+                created.push(dir);
+            }
+        }
+    }
+
+    // Files to overwrite are moved aside first, so a rollback can restore them
+    let mut steps = Vec::new();
+    let mut backups = Vec::new();
+    if options.overwrite {
+        for new_path in conflict::find_conflicts(renames).values() {
+            let backup = planner::temp_path(new_path);
+            steps.push((new_path.clone(), backup.clone()));
+            backups.push(backup);
+        }
+    }
+
+    steps.extend(plan);
+    let mut done: Vec<(&PathBuf, &PathBuf)> = Vec::new();
+    for (old_path, new_path) in steps.iter() {
+        if let Err(e) = rename_no_clobber(old_path, new_path) {
+            let mut msg = format!(
+                "Couldn't rename {} -> {}: {}",
+                old_path.display(),
+                new_path.display(),
+                e
+            );
+            msg += &rollback(&done);
+            msg += &remove_created(&created);
+            return Err(BasicError::new(msg));
+        }
+
+        done.push((old_path, new_path));
+    }
+
+    for backup in backups {
+        if let Err(e) = fs::remove_file(&backup) {
+            println!(
+                "Couldn't remove overwritten file {}: {}",
+                backup.display(),
+                e
+            );
+        }
+    }
+
+    Ok(created)
+}
+
+fn rename_no_clobber(old_path: &Path, new_path: &Path) -> Result<(), String> {
+    if path_utils::is_taken(old_path, new_path) {
+        return Err("target already exists".to_owned());
+    }
+
+    rename(old_path, new_path).map_err(|e| e.to_string())
+}
+
+fn rollback(done: &[(&PathBuf, &PathBuf)]) -> String {
+    if done.is_empty() {
+        return String::new();
+    }
+
+    let mut failed = Vec::new();
+    for (old_path, new_path) in done.iter().rev() {
+        if let Err(e) = rename_no_clobber(new_path, old_path) {
+            failed.push(format!(
+                "Couldn't restore {} -> {}: {}",
+                new_path.display(),
+                old_path.display(),
+                e
+            ));
+        }
+    }
+
+    let mut msg = format!(
+        "\nRestored {} of {} renamed files",
+        done.len() - failed.len(),
+        done.len()
+    );
+    for f in failed {
+        msg += &format!("\n\t{}", f);
+    }
+
+    msg
+}
+
+fn remove_created(created: &[PathBuf]) -> String {
+    let mut msg = String::new();
+    for dir in created.iter().rev() {
+        if let Err(e) = fs::remove_dir(dir) {
+            msg += &format!("\n\tCouldn't remove created {}: {}", dir.display(), e);
+        }
+    }
+
+    msg
+}
+
+/// Whether the current user can create and remove entries in the directory
+#[cfg(unix)]
+fn is_writable(dir: &Path) -> bool {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let Ok(dir) = CString::new(dir.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: dir is a valid nul terminated string
+    unsafe { libc::access(dir.as_ptr(), libc::W_OK | libc::X_OK) == 0 }
+}
+
+#[cfg(not(unix))]
+fn is_writable(dir: &Path) -> bool {
+    fs::metadata(dir).is_ok_and(|m| !m.permissions().readonly())
+}
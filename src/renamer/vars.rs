@@ -0,0 +1,56 @@
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    fs::{self, Metadata},
+    io,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Returns the variables describing the file, without their #
+pub fn file_vars(base: &Path, path: &Path) -> HashMap<&'static str, String> {
+    let mut vars = HashMap::new();
+    let parent = path.parent().unwrap_or(base);
+    let relative = |p: &Path| p.strip_prefix(base).unwrap_or(p).display().to_string();
+    let name = |p: Option<&OsStr>| p.map_or(String::new(), |s| s.to_string_lossy().into_owned());
+
+    vars.insert("dir", relative(parent));
+    vars.insert("path", relative(path));
+    vars.insert("stem", name(path.file_stem()));
+    vars.insert("ext", name(path.extension()));
+    vars.insert(
+        "parent",
+        if parent == base {
+            String::new()
+        } else {
+            name(parent.file_name())
+        },
+    );
+
+    let is_dir = fs::symlink_metadata(path).is_ok_and(|m| m.is_dir());
+    vars.insert("is_dir", is_dir.to_string());
+
+    // Links are described by the file they point to, if it exists
+    let metadata = fs::metadata(path)
+        .or_else(|_| fs::symlink_metadata(path))
+        .ok();
+    vars.insert(
+        "size",
+        metadata
+            .as_ref()
+            .map_or(String::new(), |m| m.len().to_string()),
+    );
+    vars.insert("ctime", unix_time(&metadata, Metadata::created));
+    vars.insert("mtime", unix_time(&metadata, Metadata::modified));
+    vars.insert("atime", unix_time(&metadata, Metadata::accessed));
+    vars
+}
+
+/// Seconds since the unix epoch, empty if the platform doesn't have this time
+fn unix_time(metadata: &Option<Metadata>, time: fn(&Metadata) -> io::Result<SystemTime>) -> String {
+    metadata
+        .as_ref()
+        .and_then(|m| time(m).ok())
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(String::new(), |d| d.as_secs().to_string())
+}
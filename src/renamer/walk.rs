@@ -0,0 +1,361 @@
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    fs::{self, Metadata},
+    path::{Path, PathBuf},
+};
+
+use clap::{Args, ValueEnum};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::{
+    gitignore::{Gitignore, GitignoreBuilder},
+    Match,
+};
+use walkdir::{DirEntry, WalkDir};
+
+use crate::{
+    errors::{BasicError, Error},
+    utils::string_utils,
+};
+
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".noraignore"];
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FileKind {
+    /// Regular files
+    #[clap(name = "f")]
+    File,
+    /// Directories
+    #[clap(name = "d")]
+    Dir,
+    /// Symbolic links
+    #[clap(name = "l")]
+    Link,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortBy {
+    /// Path, character by character
+    Name,
+    /// Path, numbers compared by value (file2 before file10)
+    Natural,
+    /// Modification time
+    Mtime,
+    /// Creation time
+    Ctime,
+    /// Size
+    Size,
+    /// Extension
+    Ext,
+}
+
+/// Options selecting the files to rename
+#[derive(Args)]
+pub struct WalkArgs {
+    /// Recursive
+    /// Recursively get files in directory
+    #[clap(short, long)]
+    pub recursive: bool,
+
+    /// Depth limit
+    /// Specify a depth limit, if not, unlimited (used with -r)
+    #[clap(short, long)]
+    pub depth_limit: Option<usize>,
+
+    /// Only rename the files matching this glob (can be repeated)
+    #[clap(long, value_name = "GLOB")]
+    pub include: Vec<String>,
+
+    /// Don't rename nor look into the files matching this glob (can be repeated)
+    #[clap(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// Include hidden files (starting with a dot)
+    #[clap(long)]
+    pub hidden: bool,
+
+    /// Kind of files to rename, files and links if not set (can be repeated)
+    #[clap(short = 't', long = "type", value_enum)]
+    pub types: Vec<FileKind>,
+
+    /// Also rename directories, what's inside a directory comes before it
+    #[clap(long)]
+    pub dirs: bool,
+
+    /// Order in which the files are interpreted, ties are ordered naturally
+    #[clap(long, value_enum, default_value = "natural")]
+    pub sort: SortBy,
+
+    /// Reverse the order of the files
+    #[clap(long)]
+    pub reverse: bool,
+
+    /// Honour .gitignore and .noraignore files
+    #[clap(long)]
+    pub ignore_files: bool,
+}
+
+pub struct Walker<'a> {
+    base: &'a Path,
+    args: &'a WalkArgs,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    ignores: RefCell<HashMap<PathBuf, Option<Gitignore>>>,
+}
+
+impl<'a> Walker<'a> {
+    pub fn new(base: &'a Path, args: &'a WalkArgs) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            base,
+            args,
+            include: build_globs(&args.include)?,
+            exclude: build_globs(&args.exclude)?,
+            ignores: RefCell::new(HashMap::new()),
+        })
+    }
+
+    /// Returns the paths of the files to rename
+    pub fn walk(&self) -> Vec<PathBuf> {
+        let max_depth = if self.args.recursive {
+            self.args.depth_limit.unwrap_or(usize::MAX)
+        } else {
+            1
+        };
+
+        let walkdir = WalkDir::new(self.base).min_depth(1).max_depth(max_depth);
+        let mut paths = Vec::new();
+        for entry in walkdir
+            .into_iter()
+            .filter_entry(|e| self.keep(e))
+            .filter_map(|e| e.ok())
+        {
+            if self.select(&entry) {
+                paths.push(entry.into_path());
+            }
+        }
+
+        self.sort(&mut paths);
+        if self.args.dirs {
+            paths = contents_first(paths);
+        }
+
+        paths
+    }
+
+    fn sort(&self, paths: &mut [PathBuf]) {
+        let metadata: HashMap<PathBuf, Metadata> = match self.args.sort {
+            SortBy::Mtime | SortBy::Ctime | SortBy::Size => paths
+                .iter()
+                .filter_map(|p| Some((p.clone(), fs::symlink_metadata(p).ok()?)))
+                .collect(),
+            _ => HashMap::new(),
+        };
+
+        paths.sort_by(|a, b| {
+            let ordering = match self.args.sort {
+                SortBy::Name => a.cmp(b),
+                SortBy::Natural => Ordering::Equal,
+                SortBy::Mtime => {
+                    let time = |p| metadata.get(p).and_then(|m: &Metadata| m.modified().ok());
+                    time(a).cmp(&time(b))
+                }
+                SortBy::Ctime => {
+                    let time = |p| metadata.get(p).and_then(|m: &Metadata| m.created().ok());
+                    time(a).cmp(&time(b))
+                }
+                SortBy::Size => {
+                    let size = |p| metadata.get(p).map(|m: &Metadata| m.len());
+                    size(a).cmp(&size(b))
+                }
+                SortBy::Ext => {
+                    let ext =
+                        |p: &PathBuf| p.extension().map(|e| e.to_string_lossy().to_lowercase());
+                    ext(a).cmp(&ext(b))
+                }
+            };
+
+            let ordering = ordering.then_with(|| natural_path_cmp(a, b));
+            if self.args.reverse {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+
+    /// Whether the entry and what's inside of it are kept
+    fn keep(&self, entry: &DirEntry) -> bool {
+        if entry.depth() == 0 {
+            return true;
+        }
+
+        if !self.args.hidden && entry.file_name().to_string_lossy().starts_with('.') {
+            return false;
+        }
+
+        if self
+            .exclude
+            .as_ref()
+            .is_some_and(|globs| self.matches(globs, entry.path()))
+        {
+            return false;
+        }
+
+        !(self.args.ignore_files && self.is_ignored(entry))
+    }
+
+    /// Whether the entry itself is renamed
+    fn select(&self, entry: &DirEntry) -> bool {
+        let file_type = entry.file_type();
+        let kind = if file_type.is_dir() {
+            FileKind::Dir
+        } else if file_type.is_symlink() {
+            FileKind::Link
+        } else {
+            FileKind::File
+        };
+
+        let kind_selected = if kind == FileKind::Dir && self.args.dirs {
+            true
+        } else if self.args.types.is_empty() {
+            kind != FileKind::Dir
+        } else {
+            self.args.types.contains(&kind)
+        };
+
+        kind_selected
+            && self
+                .include
+                .as_ref()
+                .is_none_or(|globs| self.matches(globs, entry.path()))
+    }
+
+    /// Globs are matched on the path relative to the base and on the file name
+    fn matches(&self, globs: &GlobSet, path: &Path) -> bool {
+        let relative = path.strip_prefix(self.base).unwrap_or(path);
+        globs.is_match(relative) || path.file_name().is_some_and(|name| globs.is_match(name))
+    }
+
+    /// The ignore file closest to the entry decides
+    fn is_ignored(&self, entry: &DirEntry) -> bool {
+        let is_dir = entry.file_type().is_dir();
+        for dir in entry.path().ancestors().skip(1) {
+            if !dir.starts_with(self.base) {
+                break;
+            }
+
+            let mut ignores = self.ignores.borrow_mut();
+            let ignore = ignores
+                .entry(dir.to_owned())
+                .or_insert_with(|| load_ignore(dir));
+            if let Some(ignore) = ignore {
+                match ignore.matched(entry.path(), is_dir) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => (),
+                }
+            }
+        }
+
+        false
+    }
+}
+
+/// Compares the paths component by component
+fn natural_path_cmp(a: &Path, b: &Path) -> Ordering {
+    let mut a_components = a.components();
+    let mut b_components = b.components();
+    loop {
+        match (a_components.next(), b_components.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(ac), Some(bc)) => {
+                let ordering = string_utils::natural_cmp(
+                    &ac.as_os_str().to_string_lossy(),
+                    &bc.as_os_str().to_string_lossy(),
+                );
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
+}
+
+fn load_ignore(dir: &Path) -> Option<Gitignore> {
+    let mut builder = GitignoreBuilder::new(dir);
+    let mut found = false;
+    for name in IGNORE_FILES {
+        let path = dir.join(name);
+        if path.is_file() {
+            // Invalid lines are skipped, like git does
+            let _ = builder.add(path);
+            found = true;
+        }
+    }
+
+    if !found {
+        return None;
+    }
+
+    builder.build().ok()
+}
+
+/// Moves every directory right after the last path inside it, the others keep their order
+fn contents_first(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut inside: HashMap<PathBuf, usize> = paths.iter().map(|p| (p.clone(), 0)).collect();
+    for path in &paths {
+        for dir in path.ancestors().skip(1) {
+            if let Some(count) = inside.get_mut(dir) {
+                *count += 1;
+            }
+        }
+    }
+
+    let mut waiting = HashSet::new();
+    let mut sorted = Vec::with_capacity(paths.len());
+    for path in paths {
+        if inside[&path] > 0 {
+            waiting.insert(path);
+            continue;
+        }
+
+        // Placing a path can complete a directory waiting for it, the ones above it
+        // still wait for that directory
+        let mut next = Some(path);
+        while let Some(path) = next.take() {
+            for dir in path.ancestors().skip(1) {
+                if let Some(count) = inside.get_mut(dir) {
+                    *count -= 1;
+                    if *count == 0 && waiting.remove(dir) {
+                        next = Some(dir.to_path_buf());
+                    }
+                }
+            }
+
+            sorted.push(path);
+        }
+    }
+
+    sorted
+}
+
+fn build_globs(patterns: &[String]) -> Result<Option<GlobSet>, Box<dyn Error>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern)
+            .map_err(|e| BasicError::new(format!("Invalid glob {}: {}", pattern, e)))?;
+        builder.add(glob);
+    }
+
+    let globs = builder
+        .build()
+        .map_err(|e| BasicError::new(format!("Invalid globs: {}", e)))?;
+    Ok(Some(globs))
+}
@@ -40,6 +40,9 @@ impl Lexer {
             '<' => Some(TokenType::LessThanSign),
             '>' => Some(TokenType::GreaterThanSign),
             '!' => Some(TokenType::ExclamationMark),
+            '~' => Some(TokenType::Tilde),
+            '&' => Some(TokenType::Ampersand),
+            '|' => Some(TokenType::Pipe),
             _ => None,
         };
 
@@ -65,6 +68,7 @@ impl Lexer {
         let type_o = match _type {
             TokenType::EqualSign => match next_c {
                 '=' => Some(TokenType::DoubleEqualSign),
+                '>' => Some(TokenType::Arrow),
                 _ => None,
             },
             TokenType::LessThanSign => match next_c {
@@ -79,6 +83,14 @@ impl Lexer {
                 '=' => Some(TokenType::NotEqualSign),
                 _ => None,
             },
+            TokenType::Dot => match next_c {
+                '.' => Some(TokenType::DoubleDot),
+                _ => None,
+            },
+            TokenType::Tilde => match next_c {
+                '=' => Some(TokenType::TildeEqualSign),
+                _ => None,
+            },
             TokenType::QuestionMark => match next_c {
                 '>' => Some(TokenType::QuestionMarkGreaterThan),
                 _ => None,
@@ -95,6 +107,14 @@ impl Lexer {
                 '-' => Some(TokenType::DoubleSubtraction),
                 _ => None,
             },
+            TokenType::Ampersand => match next_c {
+                '&' => Some(TokenType::DoubleAmpersand),
+                _ => None,
+            },
+            TokenType::Pipe => match next_c {
+                '|' => Some(TokenType::DoublePipe),
+                _ => None,
+            },
             _ => None,
         };
 
@@ -184,6 +204,8 @@ impl Lexer {
         let _type = match s {
             "number" => Some(TokenType::KeyNumber),
             "string" => Some(TokenType::KeyString),
+            "true" => Some(TokenType::KeyTrue),
+            "false" => Some(TokenType::KeyFalse),
             "for" => Some(TokenType::KeyFor),
             "in" => Some(TokenType::KeyIn),
             _ => None,
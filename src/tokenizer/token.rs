@@ -24,6 +24,7 @@ pub enum TokenType {
     BracketR,
 
     Dot,
+    DoubleDot,
     Comma,
     Semicolon,
     QuestionMark,
@@ -32,15 +33,24 @@ pub enum TokenType {
     EqualSign,
     DoubleEqualSign,
     NotEqualSign,
+    Tilde,
+    TildeEqualSign,
     LessThanSign,
     LessThanEqualSign,
     GreaterThanSign,
     GreaterThanEqualSign,
     QuestionMarkGreaterThan,
+    Arrow,
     DoubleSubtraction,
+    Ampersand,
+    DoubleAmpersand,
+    Pipe,
+    DoublePipe,
 
     KeyNumber,
     KeyString,
+    KeyTrue,
+    KeyFalse,
     KeyFor,
     KeyIn,
 }
@@ -3,15 +3,11 @@ use crate::{
     tokenizer::token::TokenType,
 };
 
-pub fn partial_eq<T>(token: &TokenType, l: T, r: T) -> Result<bool, Box<dyn Error>>
-where
-    T: PartialEq,
-{
-    match token {
-        TokenType::DoubleEqualSign => Ok(l == r),
-        TokenType::NotEqualSign => Ok(l != r),
-        _ => Err(BasicError::new("not a good equation".to_owned())),
-    }
+pub fn is_equality(token: &TokenType) -> bool {
+    matches!(
+        token,
+        TokenType::DoubleEqualSign | TokenType::NotEqualSign | TokenType::TildeEqualSign
+    )
 }
 
 pub fn partial_ord<T>(token: &TokenType, l: T, r: T) -> Result<bool, Box<dyn Error>>
@@ -1,3 +1,5 @@
 pub mod chain_reader;
 pub mod equal_utils;
+pub mod path_utils;
 pub mod string_utils;
+pub mod time_utils;
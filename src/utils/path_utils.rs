@@ -0,0 +1,70 @@
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+/// Resolves the `.` and `..` components of the path without touching the filesystem
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            _ => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/// Returns the ancestors of the path that don't exist, the top most first
+pub fn missing_ancestors(path: &Path) -> Vec<PathBuf> {
+    let mut missing: Vec<PathBuf> = path
+        .ancestors()
+        .skip(1)
+        .take_while(|dir| !dir.as_os_str().is_empty() && !dir.exists())
+        .map(|dir| dir.to_owned())
+        .collect();
+    missing.reverse();
+    missing
+}
+
+/// Displays the path relative to base
+pub fn display(base: &Path, path: &Path) -> String {
+    path.strip_prefix(base)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// Whether the target of a rename is taken by another file than the source
+pub fn is_taken(old_path: &Path, new_path: &Path) -> bool {
+    fs::symlink_metadata(new_path).is_ok() && !is_same_file(old_path, new_path)
+}
+
+/// Whether both paths are the same file, like readme.md and README.md on a case insensitive filesystem
+/// Hard links with different names are not the same file, renaming one to the other would do nothing
+pub fn is_same_file(a: &Path, b: &Path) -> bool {
+    if a.to_string_lossy().to_lowercase() != b.to_string_lossy().to_lowercase() {
+        return false;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        match (fs::symlink_metadata(a), fs::symlink_metadata(b)) {
+            (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+            _ => false,
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        match (fs::canonicalize(a), fs::canonicalize(b)) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+    }
+}
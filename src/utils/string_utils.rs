@@ -1,4 +1,4 @@
-use std::fmt::Debug;
+use std::{cmp::Ordering, fmt::Debug, iter::Peekable, str::Chars};
 
 pub fn is_identifer(c: char) -> bool {
     c.is_alphabetic() || c.is_alphanumeric() || c == '_' || c == '#'
@@ -13,9 +13,53 @@ where
     }
 
     let mut s = format!("{:?}", v[0]);
-    for i in 1..v.len() {
-        s += &format!("{}{:?}", sep, v[i]);
+    for item in v.iter().skip(1) {
+        s += &format!("{}{:?}", sep, item);
     }
 
     s
 }
+
+/// Compares strings the way humans do, numbers inside of them are compared by value
+/// ex: "file2" < "file10"
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+    loop {
+        let ordering = match (a_chars.peek(), b_chars.peek()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(ac), Some(bc)) if ac.is_ascii_digit() && bc.is_ascii_digit() => {
+                let a_number = take_number(&mut a_chars);
+                let b_number = take_number(&mut b_chars);
+                let a_trimmed = a_number.trim_start_matches('0');
+                let b_trimmed = b_number.trim_start_matches('0');
+                a_trimmed
+                    .len()
+                    .cmp(&b_trimmed.len())
+                    .then_with(|| a_trimmed.cmp(b_trimmed))
+                    .then_with(|| a_number.len().cmp(&b_number.len()))
+            }
+            (Some(ac), Some(bc)) => {
+                let ordering = ac.to_lowercase().cmp(bc.to_lowercase());
+                a_chars.next();
+                b_chars.next();
+                ordering
+            }
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+fn take_number(chars: &mut Peekable<Chars>) -> String {
+    let mut number = String::new();
+    while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+        number.push(c);
+    }
+
+    number
+}